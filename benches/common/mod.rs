@@ -0,0 +1,205 @@
+//! what every bench shares: the generated sources and a small criterion style harness. criterion itself drags in
+//! serde, clap, regex and friends, which is a lot of build for timing a loop
+//!
+//! each benchmark warms up, then takes a batch of samples and reports the median time per run with the spread
+//! (median absolute deviation) and throughput. medians get saved under target/tmp/bench/<baseline>/, and the
+//! next run compares against them, so numbers carry over between commits:
+//!
+//!     cargo bench -- --save-baseline main      (on the commit to compare against)
+//!     cargo bench -- --baseline main           (on the new one, compares without overwriting)
+//!
+//! with neither, every run compares against and then replaces the last one. anything else on the command line
+//! filters benchmarks by name (`cargo bench -- parse/100K`)
+//!
+//! 10M lines is around 16G of memory by the time it's parsed and resolved, so on a smaller machine stick to
+//! `cargo bench -- 1K 100K`
+#![allow(dead_code)] // each bench only uses some of this
+
+use std::{
+    env::args,
+    fs::{create_dir_all, read_to_string, write},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// the source sizes every stage gets run over, by name and line count
+pub const SIZES: &[(&str, usize)] = &[("1K", 1_000), ("100K", 100_000), ("10M", 10_000_000)];
+
+// how long to run something before measuring, and roughly how long to spend measuring it
+const WARM_UP: Duration = Duration::from_secs(1);
+const MEASURE: Duration = Duration::from_secs(5);
+
+// sample count bounds. big inputs take seconds a run, so they get fewer (that's what the spread is for)
+const MIN_SAMPLES: usize = 5;
+const MAX_SAMPLES: usize = 50;
+
+// a change smaller than this is noise as far as the report's concerned
+const NOISE: f64 = 0.02;
+
+/// a valid stick program `lines` lines long, the same every time for the same length. it leans on expressions
+/// (that's most of what real code is) with declarations, assignments, calls, fields, indexing, imports and
+/// comments mixed in. every name is declared before it's used, so resolution takes the normal path instead of
+/// the typo suggestion one
+pub fn generate(lines: usize) -> String {
+    let mut src: String = String::with_capacity(lines * 40);
+    src.push_str("from std::io import print, open\n");
+
+    // everything declared so far, and the ones that can be assigned to
+    let mut names: Vec<usize> = vec![0];
+    let mut mutables: Vec<usize> = vec![0];
+    src.push_str("let mutable v0: i32 = 0\n");
+
+    // small lcg so which names get used varies without depending on anything outside this file
+    let mut seed: u64 = 0x5717c;
+    let mut pick = |from: &[usize]| -> usize {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        from[(seed >> 33) as usize % from.len()]
+    };
+
+    for i in 2..lines.max(2) {
+        let (a, b, c): (usize, usize, usize) = (pick(&names), pick(&names), pick(&names));
+        let m: usize = pick(&mutables);
+        let line: String = match i % 10 {
+            0 => format!("let mutable v{i}: i32 = (v{a} + {i}) * v{b} - v{c} / 2 % 7\n"),
+            1 => format!("v{m} += print(v{b}, v{c}) ** 2\n"),
+            2 => format!("let mutable v{i} = open(\"log\").field.method(1, 2.5, 'c')[v{a}..v{b}]\n"),
+            3 => format!("v{m} = v{b} + (v{c} << 2) & ~{i} | v{a} ^ 1\n"),
+            4 => format!("/// doc comment for v{i}\nlet const v{i}: u64 = not v{a} and (v{b} >= {i} or v{c} != 0)\n"),
+            5 => format!("let mutable v{i}: f64 = v{a}[v{b}][{i}].x * -v{c}\n"),
+            6 => format!("// plain comment about v{a}\nv{m} -= v{c}\n"),
+            7 => format!("let mutable v{i}: str = \"string number {i}\"\n"),
+            8 => format!("v{a}.y = print(open(v{b}), v{c})\n"),
+            _ => format!("let mutable v{i} = v{a} == v{b} or v{c} < {i}; v{m} *= 3\n"),
+        };
+        src.push_str(&line);
+
+        // the lets declare v{i}, all of them mutable but the const
+        if matches!(i % 10, 0 | 2 | 4 | 5 | 7 | 9) {
+            names.push(i);
+            if i % 10 != 4 {
+                mutables.push(i);
+            }
+        }
+    }
+    src
+}
+
+/// times things and reports them, see the top of the file
+pub struct Bench {
+    filter: Vec<String>,
+    dir: PathBuf,
+    save: bool,
+}
+
+impl Bench {
+    /// set up from whatever cargo bench passed along
+    pub fn from_args() -> Bench {
+        let mut filter: Vec<String> = Vec::new();
+        let mut baseline: Option<(String, bool)> = None;
+        let mut args = args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--save-baseline" => baseline = args.next().map(|name| (name, true)),
+                "--baseline" => baseline = args.next().map(|name| (name, false)),
+
+                // cargo passes --bench, and anything else with a dash is meant for some other harness
+                a if a.starts_with('-') => {}
+                _ => filter.push(arg),
+            }
+        }
+
+        let (name, save): (String, bool) = baseline.unwrap_or(("last".to_string(), true));
+        Bench {
+            filter,
+            dir: PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bench").join(name),
+            save,
+        }
+    }
+
+    /// whether a benchmark is going to run at all (so its input doesn't get built for nothing)
+    pub fn wants(&self, name: &str) -> bool {
+        self.filter.is_empty() || self.filter.iter().any(|f| name.contains(f.as_str()))
+    }
+
+    /// time `routine`, which chews through `bytes` of source a run
+    pub fn run<T>(&self, name: &str, bytes: usize, mut routine: impl FnMut() -> T) {
+        if !self.wants(name) {
+            return;
+        }
+
+        // warm up, which also says how long a run takes
+        let start: Instant = Instant::now();
+        let mut runs: u32 = 0;
+        while runs == 0 || start.elapsed() < WARM_UP {
+            drop(std::hint::black_box(routine()));
+            runs += 1;
+        }
+        let each: Duration = start.elapsed() / runs;
+
+        // quick things get batched so a sample is long enough for the clock to mean something
+        let batch: u32 = (Duration::from_millis(1).as_nanos() / each.as_nanos().max(1)).max(1) as u32;
+        let samples: usize =
+            ((MEASURE.as_nanos() / (each.as_nanos() * batch as u128).max(1)) as usize).clamp(MIN_SAMPLES, MAX_SAMPLES);
+
+        let mut times: Vec<f64> = Vec::with_capacity(samples);
+        for _ in 0..samples {
+            let start: Instant = Instant::now();
+            for _ in 0..batch {
+                drop(std::hint::black_box(routine()));
+            }
+            times.push(start.elapsed().as_secs_f64() / batch as f64);
+        }
+
+        let typical: f64 = median(&mut times);
+        let mut deviations: Vec<f64> = times.iter().map(|t| (t - typical).abs()).collect();
+        let spread: f64 = median(&mut deviations) / typical * 100.0;
+        let throughput: f64 = bytes as f64 / typical / (1024.0 * 1024.0);
+        println!(
+            "{name:<16} time: {:>10} ± {spread:>5.2}%   thrpt: {throughput:>8.2} MiB/s   ({samples} samples){}",
+            show(typical),
+            self.compare(name, typical),
+        );
+    }
+
+    // how this run stacks up against the baseline, and save it if that's what we're doing
+    fn compare(&self, name: &str, median: f64) -> String {
+        let path: PathBuf = self.dir.join(name.replace('/', "-"));
+        let change: String = match read_to_string(&path).ok().and_then(|s| s.trim().parse::<f64>().ok()) {
+            Some(before) => {
+                let change: f64 = (median - before) / before;
+                let verdict: &str = match change {
+                    c if c.abs() < NOISE => "no change",
+                    c if c < 0.0 => "faster",
+                    _ => "slower",
+                };
+                format!("   {:+.2}% {verdict} (was {})", change * 100.0, show(before))
+            }
+            None => String::new(),
+        };
+
+        if self.save {
+            let _ = create_dir_all(&self.dir);
+            let _ = write(&path, format!("{median}\n"));
+        }
+        change
+    }
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid: usize = values.len() / 2;
+    match values.len() % 2 {
+        0 => (values[mid - 1] + values[mid]) / 2.0,
+        _ => values[mid],
+    }
+}
+
+// seconds in whatever unit reads best
+fn show(secs: f64) -> String {
+    match secs {
+        s if s < 1e-6 => format!("{:.2} ns", s * 1e9),
+        s if s < 1e-3 => format!("{:.2} µs", s * 1e6),
+        s if s < 1.0 => format!("{:.2} ms", s * 1e3),
+        s => format!("{s:.3} s"),
+    }
+}
@@ -0,0 +1,20 @@
+//! `lexer::lex` over the generated sources: `cargo bench --bench lex`
+mod common;
+
+use common::{Bench, SIZES, generate};
+use stick::{cli::Flags, lexer::lex, source::SourceMap};
+
+fn main() {
+    let bench: Bench = Bench::from_args();
+    for &(size, lines) in SIZES {
+        let name: String = format!("lex/{size}");
+        if !bench.wants(&name) {
+            continue;
+        }
+
+        let mut sources: SourceMap = SourceMap::new();
+        let id = sources.add(format!("{size}.stick"), generate(lines));
+        let file = sources.get(id);
+        bench.run(&name, file.src.len(), || lex(file, &Flags::QUIET));
+    }
+}
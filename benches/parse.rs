@@ -0,0 +1,22 @@
+//! `Parser::parse` over the generated sources: `cargo bench --bench parse`. lexing happens once up front, so the
+//! numbers are just the parser building the tree
+mod common;
+
+use common::{Bench, SIZES, generate};
+use stick::{cli::Flags, lexer::lex, parser::Parser, source::SourceMap};
+
+fn main() {
+    let bench: Bench = Bench::from_args();
+    for &(size, lines) in SIZES {
+        let name: String = format!("parse/{size}");
+        if !bench.wants(&name) {
+            continue;
+        }
+
+        let mut sources: SourceMap = SourceMap::new();
+        let id = sources.add(format!("{size}.stick"), generate(lines));
+        let file = sources.get(id);
+        let lexed = lex(file, &Flags::QUIET).unwrap_or_else(|_| panic!("generated source should lex"));
+        bench.run(&name, file.src.len(), || Parser::new(file, &lexed).parse(&Flags::QUIET));
+    }
+}
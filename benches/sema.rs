@@ -0,0 +1,25 @@
+//! name resolution over the generated sources: `cargo bench --bench sema`. lexing and parsing happen once up
+//! front. codegen gets a bench of its own once there's a backend to run
+mod common;
+
+use common::{Bench, SIZES, generate};
+use stick::{cli::Flags, lexer::lex, parser::Parser, sema::resolve, source::SourceMap};
+
+fn main() {
+    let bench: Bench = Bench::from_args();
+    for &(size, lines) in SIZES {
+        let name: String = format!("sema/{size}");
+        if !bench.wants(&name) {
+            continue;
+        }
+
+        let mut sources: SourceMap = SourceMap::new();
+        let id = sources.add(format!("{size}.stick"), generate(lines));
+        let file = sources.get(id);
+        let lexed = lex(file, &Flags::QUIET).unwrap_or_else(|_| panic!("generated source should lex"));
+        let (ast, errors) = Parser::new(file, &lexed).parse(&Flags::QUIET);
+        assert!(errors.is_empty(), "generated source should parse cleanly");
+        drop(lexed);
+        bench.run(&name, file.src.len(), || resolve(file, &ast));
+    }
+}
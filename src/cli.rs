@@ -0,0 +1,250 @@
+//! command line parsing. everything you can pass ends up in one Options, and the rest of main just reads from it
+use crate::{
+    emit::Emit,
+    error::{ErrorFormat, Lints, lint::LintLevel},
+    parallel, usage,
+};
+use std::env::args;
+
+// exit codes, so scripts can tell what went wrong without reading the output
+/// the code has errors (or `fmt --check` wants to change it)
+pub const EXIT_COMPILE: i32 = 1;
+/// bad arguments, or asking for something the compiler can't do yet
+pub const EXIT_USAGE: i32 = 2;
+/// couldn't read or write a file
+pub const EXIT_IO: i32 = 3;
+/// the compiler did something it never should (a bug, please report it)
+pub const EXIT_INTERNAL: i32 = 4;
+
+/// the switches the lexer and parser care about
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Flags {
+    /// print every token and node as it's made, and dump errors to lastrun.log
+    pub debug: bool,
+
+    /// stop at the first syntax error
+    pub fastfail: bool,
+
+    /// no "Lexed N bytes"/"Parsed N tokens" timing lines
+    pub quiet: bool,
+
+    /// pause between stages until enter is pressed
+    pub step: bool,
+}
+
+impl Flags {
+    /// for tools that only want the results back (fmt, the lsp, ...)
+    pub const QUIET: Flags = Flags {
+        debug: false,
+        fastfail: false,
+        quiet: true,
+        step: false,
+    };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// lex, parse and resolve, then report (what you get with no subcommand)
+    Check,
+    Build,
+    Run,
+    Fmt,
+    Fix,
+    Doc,
+    Repl,
+    Lsp,
+    Explain(Option<String>),
+    New(Option<String>),
+}
+
+impl Command {
+    fn from_name(name: &str) -> Option<Command> {
+        match name {
+            "check" => Some(Command::Check),
+            "build" => Some(Command::Build),
+            "run" => Some(Command::Run),
+            "fmt" => Some(Command::Fmt),
+            "fix" => Some(Command::Fix),
+            "doc" => Some(Command::Doc),
+            "repl" => Some(Command::Repl),
+            "lsp" => Some(Command::Lsp),
+            _ => None,
+        }
+    }
+
+    /// whether this one works on a file (the rest run on their own, check/build/run/doc fall back to the project
+    /// they're in)
+    pub fn needs_path(&self) -> bool {
+        !matches!(
+            self,
+            Command::Check
+                | Command::Build
+                | Command::Run
+                | Command::Doc
+                | Command::Repl
+                | Command::Lsp
+                | Command::Explain(_)
+                | Command::New(_)
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub command: Command,
+    pub path: Option<String>,
+    pub flags: Flags,
+    pub lints: Lints,
+    pub format: ErrorFormat,
+
+    /// stop after this stage and write out what it made
+    pub emit: Option<Emit>,
+
+    /// where output goes (-o), stdout or a default spot otherwise
+    pub out: Option<String>,
+
+    /// `fmt --check`
+    pub check_only: bool,
+
+    /// build with the release profile instead of debug
+    pub release: bool,
+
+    /// how many modules get compiled at once (-j), one per core by default
+    pub jobs: usize,
+
+    /// `check --watch`, check again every time a file changes
+    pub watch: bool,
+}
+
+impl Options {
+    /// read everything off the command line, bailing out with usage on anything it doesn't understand
+    pub fn parse() -> Options {
+        let mut args = args().skip(1);
+        let mut opts: Options = Options {
+            command: Command::Check,
+            path: None,
+            flags: Flags::default(),
+            lints: Lints::default(),
+            format: ErrorFormat::Human,
+            emit: None,
+            out: None,
+            check_only: false,
+            release: false,
+            jobs: parallel::default_jobs(),
+            watch: false,
+        };
+        let mut command: Option<Command> = None;
+
+        while let Some(a) = args.next() {
+            match a.as_str() {
+                // subcommands only count before the file
+                s if command.is_none() && opts.path.is_none() && Command::from_name(s).is_some() => {
+                    command = Command::from_name(s);
+                }
+                "explain" if command.is_none() && opts.path.is_none() => {
+                    command = Some(Command::Explain(args.next()));
+                }
+                "--explain" => command = Some(Command::Explain(args.next())),
+                "new" if command.is_none() && opts.path.is_none() => {
+                    command = Some(Command::New(args.next()));
+                }
+
+                "-d" | "--debug" => opts.flags.debug = true,
+                "-ff" | "--fastfail" => opts.flags.fastfail = true,
+                "-q" | "--quiet" => opts.flags.quiet = true,
+                "--step" => opts.flags.step = true,
+                "--check" => opts.check_only = true,
+                "--release" => opts.release = true,
+                "-w" | "--watch" => opts.watch = true,
+
+                // -j 4, -j4 or --jobs 4
+                s if s == "--jobs" || s.starts_with("-j") => {
+                    let n: String = match s.strip_prefix("-j").filter(|n| !n.is_empty()) {
+                        Some(n) => n.to_string(),
+                        None => args.next().unwrap_or_default(),
+                    };
+                    opts.jobs = match n.parse::<usize>() {
+                        Ok(n) if n > 0 => n,
+                        _ => usage!("{s} needs a number of threads above 0, got '{n}'\n"),
+                    };
+                }
+
+                "-o" | "--out" => {
+                    opts.out = Some(args.next().unwrap_or_else(|| {
+                        usage!("{a} needs a path\n");
+                    }));
+                }
+
+                // --emit=ast, --emit ast, or with somewhere to put it like rustc does: --emit=ast=out.txt
+                s if s.starts_with("--emit") => {
+                    let spec: String = match s.strip_prefix("--emit=") {
+                        Some(spec) => spec.to_string(),
+                        None => args.next().unwrap_or_default(),
+                    };
+                    let (name, out): (&str, Option<&str>) = match spec.split_once('=') {
+                        Some((name, out)) => (name, Some(out)),
+                        None => (&spec, None),
+                    };
+                    opts.emit = Some(Emit::from_name(name).unwrap_or_else(|| {
+                        usage!("unknown stage to emit: {name} (expected tokens, ast, ast-json, typed-ast, ir or bytecode)\n");
+                    }));
+                    if let Some(out) = out {
+                        opts.out = Some(out.to_string());
+                    }
+                }
+
+                // --error-format=json or --error-format json
+                s if s.starts_with("--error-format") => {
+                    let name: String = match s.strip_prefix("--error-format=") {
+                        Some(name) => name.to_string(),
+                        None => args.next().unwrap_or_default(),
+                    };
+                    opts.format = ErrorFormat::from_name(&name).unwrap_or_else(|| {
+                        usage!("unknown error format: {name} (expected human, json or sarif)\n");
+                    });
+                }
+
+                // lint levels, same idea as rustc (-W warn, -A allow, -D deny)
+                "-W" | "-A" | "-D" => {
+                    let level: LintLevel = match a.as_str() {
+                        "-W" => LintLevel::Warn,
+                        "-A" => LintLevel::Allow,
+                        _ => LintLevel::Deny,
+                    };
+                    let name: String = args.next().unwrap_or_else(|| {
+                        usage!("{a} needs a lint name\n");
+                    });
+                    if !opts.lints.set(&name, level) {
+                        usage!("unknown lint: {name}\n");
+                    }
+                }
+
+                "--" => {
+                    if let Some(p) = args.next() {
+                        opts.path = Some(p);
+                    }
+                    break;
+                }
+
+                // unknown args
+                s if s.starts_with('-') => usage!("unknown flag: {}\n", s),
+                s => match opts.path {
+                    None => opts.path = Some(s.to_string()),
+                    Some(_) => usage!("only one file at a time for now, got another: {s}\n"),
+                },
+            }
+        }
+
+        opts.command = command.unwrap_or(Command::Check);
+        if opts.watch && opts.command != Command::Check {
+            usage!("--watch only works with check\n");
+        }
+        if opts.watch && opts.format != ErrorFormat::Human {
+            usage!("--watch clears the screen between checks, so it only prints human readable errors\n");
+        }
+        if opts.command.needs_path() && opts.path.is_none() {
+            usage!();
+        }
+        opts
+    }
+}
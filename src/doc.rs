@@ -0,0 +1,155 @@
+//! `stick doc`: markdown api docs for a module, built from the `///` and `/** */` comments on each item. a project
+//! gets one page per module, `a::b` at a/b.md, so types from other modules can link straight to them
+use crate::parser::ast::*;
+use std::fmt::Write;
+
+/// the text of a doc comment without the comment markers. `///` loses the marker and one space after it,
+/// `/** */` loses both ends plus the ` * ` gutter people like to line their block comments up with
+pub fn doc_text(doc: &[String]) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for comment in doc {
+        let comment: &str = comment.as_str();
+        match comment.strip_prefix("///") {
+            Some(line) => lines.push(line.strip_prefix(' ').unwrap_or(line).trim_end()),
+            None => {
+                let body: &str = comment.trim_start_matches("/**").trim_end_matches("*/");
+                for line in body.lines() {
+                    let line: &str = line.trim();
+                    let line: &str = line.strip_prefix('*').map_or(line, |l| l.strip_prefix(' ').unwrap_or(l));
+                    lines.push(line);
+                }
+            }
+        }
+    }
+
+    // blank lines at either end are just the comment's own padding
+    while lines.first().is_some_and(|l| l.is_empty()) {
+        lines.remove(0);
+    }
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+// github style heading anchors, which is all the names we have can produce (lowercase, no punctuation but _ and -)
+fn anchor(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// where a module's page goes, relative to the docs folder: `a::b` is a/b.md
+pub fn page(module: &str) -> String {
+    format!("{}.md", module.replace("::", "/"))
+}
+
+// what a page can link to: the items on it, and the other modules that get a page
+struct Links<'a> {
+    items: &'a [&'a str],
+    pages: &'a [&'a str],
+
+    // back up to the docs folder from this page, `../` for every folder it's down
+    up: String,
+}
+
+// every named type a type mentions, as markdown. `a::b::C` points at C on a/b.md when that module is getting a
+// page, plain names point at an item on this page when there is one. anything else is just the name
+fn references(typ: &Type, links: &Links<'_>, out: &mut Vec<String>) {
+    match &typ.kind {
+        TypeKind::Path(parts) => {
+            let path: Vec<&str> = parts.iter().map(|p| p.as_str()).collect();
+            let (name, module) = path.split_last().expect("paths always have at least one part");
+            let module: String = module.join("::");
+            match links.pages.contains(&module.as_str()) {
+                true => out.push(format!("[`{}`]({}{}#{})", path.join("::"), links.up, page(&module), anchor(name))),
+                false => out.push(format!("`{}`", path.join("::"))),
+            }
+        }
+        TypeKind::Ident(name) if links.items.contains(&name.as_str()) => {
+            out.push(format!("[`{name}`](#{})", anchor(name.as_str())))
+        }
+        TypeKind::Ident(name) => out.push(format!("`{name}`")),
+        TypeKind::Tuple(types) => types.iter().for_each(|t| references(t, links, out)),
+        TypeKind::Array { typ, .. } => references(typ, links, out),
+        TypeKind::Func { params, ret } => {
+            params.iter().for_each(|t| references(t, links, out));
+            references(ret, links, out);
+        }
+        _ => {}
+    }
+}
+
+/// the docs page for one module: a table of contents, then each top level item with its signature, doc text and
+/// links to any named types in it. items without docs still get listed, the signature alone is worth something.
+/// pages is every module that's getting documented alongside this one (by name, `a::b`)
+pub fn document(module: &str, ast: &Ast, pages: &[&str]) -> (String, usize) {
+    let decls: Vec<&Stmt> = ast.stmts.iter().filter(|s| matches!(s.kind, StmtKind::VarDecl { .. })).collect();
+    let items: Vec<&str> = decls
+        .iter()
+        .filter_map(|s| match &s.kind {
+            StmtKind::VarDecl { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let links: Links<'_> = Links {
+        items: &items,
+        pages,
+        up: "../".repeat(module.matches("::").count()),
+    };
+
+    let mut out: String = format!("# module `{module}`\n\n");
+    if !items.is_empty() {
+        out.push_str("## Contents\n\n");
+        for name in &items {
+            let _ = writeln!(out, "- [`{name}`](#{})", anchor(name));
+        }
+    }
+
+    for stmt in decls {
+        let StmtKind::VarDecl {
+            name,
+            typ,
+            init,
+            mutable,
+            constant,
+            global,
+            doc,
+            ..
+        } = &stmt.kind
+        else {
+            continue;
+        };
+
+        // written the way it was declared, but only constants show their value (everything else can change)
+        let mut signature: String = String::from("let");
+        for (set, word) in [(*constant, " const"), (*global, " static"), (*mutable, " mutable")] {
+            if set {
+                signature.push_str(word);
+            }
+        }
+        let _ = write!(signature, " {name}");
+        if typ.span.start != typ.span.end {
+            let _ = write!(signature, ": {typ}");
+        }
+        if let (true, Some(init)) = (*constant, init) {
+            let _ = write!(signature, " = {}", ast.show(init));
+        }
+
+        let _ = write!(out, "\n## `{name}`\n\n```stick\n{signature}\n```\n");
+        let text: String = doc_text(doc);
+        if !text.is_empty() {
+            let _ = writeln!(out, "\n{text}");
+        }
+
+        let mut refs: Vec<String> = Vec::new();
+        references(typ, &links, &mut refs);
+        if !refs.is_empty() {
+            let _ = writeln!(out, "\n**Type:** {}", refs.join(", "));
+        }
+    }
+
+    (out, items.len())
+}
@@ -0,0 +1,67 @@
+//! `--emit=<stage>`: print what one stage of the compiler made and stop there, for scripts, golden tests and
+//! poking at the compiler without the debug spew
+use crate::{json::Json, lexer::lex::Lexed, parser::ast::Ast};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    Tokens,
+    Ast,
+    AstJson,
+    TypedAst,
+    Ir,
+    Bytecode,
+}
+
+impl Emit {
+    pub fn from_name(name: &str) -> Option<Emit> {
+        match name {
+            "tokens" => Some(Emit::Tokens),
+            "ast" => Some(Emit::Ast),
+            "ast-json" => Some(Emit::AstJson),
+            "typed-ast" => Some(Emit::TypedAst),
+            "ir" => Some(Emit::Ir),
+            "bytecode" => Some(Emit::Bytecode),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Emit::Tokens => "tokens",
+            Emit::Ast => "ast",
+            Emit::AstJson => "ast-json",
+            Emit::TypedAst => "typed-ast",
+            Emit::Ir => "ir",
+            Emit::Bytecode => "bytecode",
+        }
+    }
+
+    /// whether the compiler actually gets far enough to make this yet (no types, ir or codegen so far)
+    pub fn implemented(self) -> bool {
+        matches!(self, Emit::Tokens | Emit::Ast | Emit::AstJson)
+    }
+}
+
+/// one token a line, `start..end Token`
+pub fn tokens(lexed: &Lexed<'_>) -> String {
+    let mut out: String = String::new();
+    for (tok, span) in lexed.tokens.iter().zip(&lexed.spans) {
+        let _ = writeln!(out, "{span:?} {tok:?}");
+    }
+    out
+}
+
+/// the tree printed back as source, one top level statement a line
+pub fn ast(ast: &Ast) -> String {
+    let mut out: String = String::new();
+    for stmt in &ast.stmts {
+        let _ = writeln!(out, "{}", ast.show(stmt));
+    }
+    out
+}
+
+/// the whole tree as one json array of statements
+pub fn ast_json(ast: &Ast) -> String {
+    format!("{}\n", Json::Arr(ast.stmts.iter().map(|s| s.to_json(ast)).collect()))
+}
@@ -1,72 +1,318 @@
-use super::{ParseError, SyntaxError};
-use ariadne::{Color, Label, Report, ReportKind, Source};
+use super::{LexError, ManifestError, ParseError, SemaError, SyntaxError, Warning};
+use crate::source::{FileId, SourceFile, SourceMap, Span};
+use ariadne::{Cache, Color, Report, ReportKind, Source};
 use std::{
-    fmt::{Display, Formatter, Result},
+    fmt::{self, Display},
     fs::File,
     io::{self, BufWriter, Write},
     ops::Range,
 };
 use strip_ansi_escapes::strip;
 
+/// how bad a diagnostic is. only errors stop compilation
+#[allow(dead_code)] // nothing emits standalone notes or help yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+impl Severity {
+    // the colour the report header gets
+    fn color(self) -> Color {
+        match self {
+            Severity::Error => Color::Red,
+            Severity::Warning => Color::Yellow,
+            Severity::Note => Color::Cyan,
+            Severity::Help => Color::Green,
+        }
+    }
+}
+
+/// an extra span pointing at something related to the error ("first declared here").
+/// the span says which file, so it can point into a different one than the main error
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// a machine applicable edit: replace whatever is in span with replacement (empty span = insert, empty replacement = delete).
+/// only safe fixes get applied by `stick fix`, the rest are just shown as suggestions
+#[derive(Debug, Clone)]
+pub struct Fix {
+    pub span: Span,
+    pub replacement: String,
+    pub message: String,
+    pub safe: bool,
+}
+
 /// a structured way to print diagnostics. probably not struct required but is clean. will use for both lex and parse error likely
-/// - path = the file path, displayed in the error message
-/// - src = the source file, to scan for the error message
-/// - span = the range of chars the error lies in
+/// - span = the file and range of bytes the error lies in (the primary label). the SourceMap it came from has the rest
+///   (`render` needs it back to print anything)
 /// - err = the accompanying SyntaxError
+/// - severity = error, warning, etc. (lints can bump a warning up to an error)
+/// - primary = custom text for the primary label, "error here" and friends otherwise
+/// - labels/notes/helps = any amount of secondary labels, `note:` lines and extra `help:` lines
+/// - fixes = edits that would make this go away (each span says which file it edits)
 ///
-/// - <'a> the lifetime of this Diagnostic
-/// - <'src> the lifetime of the source file
-pub struct Diagnostic<'a, 'src> {
-    pub path: &'a str,
-    pub src: &'src str,
-    pub span: Range<usize>,
-    pub err: SyntaxError<'src>,
+/// nothing in here borrows the source, errors own (or intern) the text they're about, so a diagnostic can outlive
+/// the buffer it came from
+pub struct Diagnostic {
+    pub span: Span,
+    pub err: SyntaxError,
+    pub severity: Severity,
+    pub primary: Option<String>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub helps: Vec<String>,
+    pub fixes: Vec<Fix>,
+}
+
+impl Diagnostic {
+    pub fn new(span: Span, err: SyntaxError, severity: Severity) -> Self {
+        Diagnostic {
+            span,
+            err,
+            severity,
+            primary: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            helps: Vec::new(),
+            fixes: Vec::new(),
+        }
+    }
+
+    /// the error message with the ansi codes stripped, for anything that isn't a terminal.
+    /// (strum's AsRefStr makes the error an AsRef<str> too, so strip_str(&err) would give the variant name)
+    pub fn message(&self) -> String {
+        let message: String = self.err.to_string();
+        strip_ansi_escapes::strip_str(message)
+    }
+
+    /// swap out the "error here" text on the main span
+    pub fn with_primary(mut self, message: impl Into<String>) -> Self {
+        self.primary = Some(message.into());
+        self
+    }
+
+    /// secondary label, in this file or somewhere else entirely
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    /// the file the error is in
+    pub fn file(&self) -> FileId {
+        self.span.file
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// extra help on top of the hint every error kind already has
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.helps.push(help.into());
+        self
+    }
+
+    /// an edit `stick fix` is allowed to make on its own
+    pub fn with_fix(mut self, span: Span, replacement: impl Into<String>, message: impl Into<String>) -> Self {
+        self.fixes.push(Fix {
+            span,
+            replacement: replacement.into(),
+            message: message.into(),
+            safe: true,
+        });
+        self
+    }
+
+    /// an edit that probably works but changes what the code means, so it's only ever suggested
+    pub fn with_suggestion(
+        mut self,
+        span: Span,
+        replacement: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        self.fixes.push(Fix {
+            span,
+            replacement: replacement.into(),
+            message: message.into(),
+            safe: false,
+        });
+        self
+    }
 }
 
 // hacky way to avoid defining names for every type
-impl<'src> SyntaxError<'src> {
+impl SyntaxError {
     pub fn name(&self) -> &str {
         match self {
             SyntaxError::Lex(e) => e.as_ref(),
             SyntaxError::Parse(e) => e.as_ref(),
+            SyntaxError::Sema(e) => e.as_ref(),
+            SyntaxError::Manifest(e) => e.as_ref(),
+            SyntaxError::Warning(w) => w.as_ref(),
             SyntaxError::Unknown => "Unknown",
         }
     }
 
+    /// stable code for every kind of error, shown in the header and looked up by `explain`.
+    /// NEVER renumber these, only add new ones (E00xx lex/parse, E01xx sema, E02xx manifest, W00xx warnings)
+    pub fn code(&self) -> &'static str {
+        match self {
+            SyntaxError::Lex(e) => match e {
+                LexError::UnterminatedString(_) => "E0001",
+                LexError::UnterminatedChar(_) => "E0002",
+                LexError::UnknownToken(_) => "E0003",
+            },
+            SyntaxError::Parse(e) => match e {
+                ParseError::MissingName => "E0010",
+                ParseError::MissingType => "E0011",
+                ParseError::CannotInfer => "E0012",
+                ParseError::MissingValue => "E0013",
+                ParseError::MissingTerminator => "E0014",
+                ParseError::ConstMutable => "E0015",
+                ParseError::ConstStatic => "E0016",
+                ParseError::UnexpectedToken(_) => "E0017",
+                ParseError::UnexpectedEof => "E0018",
+                ParseError::MissingOperand => "E0019",
+                ParseError::MissingField => "E0020",
+                ParseError::UnclosedCall => "E0021",
+                ParseError::UnclosedParen => "E0022",
+                ParseError::UnclosedIndex => "E0023",
+                ParseError::InvalidAssignment => "E0024",
+                ParseError::Unsupported(_) => "E0025",
+                ParseError::MissingModule => "E0026",
+                ParseError::MissingImport => "E0027",
+            },
+            SyntaxError::Sema(e) => match e {
+                SemaError::DuplicateDeclaration(_) => "E0100",
+                SemaError::AssignToImmutable(_) => "E0101",
+                SemaError::UnresolvedName(_) => "E0102",
+                SemaError::UnknownType(_) => "E0103",
+                SemaError::UnresolvedModule(_) => "E0104",
+                SemaError::UnresolvedImport(_) => "E0105",
+            },
+            SyntaxError::Manifest(e) => match e {
+                ManifestError::Malformed => "E0200",
+                ManifestError::InvalidValue(_) => "E0201",
+                ManifestError::UnknownKey(_) => "E0202",
+                ManifestError::DuplicateKey(_) => "E0203",
+                ManifestError::MissingKey(_) => "E0204",
+                ManifestError::WrongType(_) => "E0205",
+                ManifestError::MissingPath(_) => "E0206",
+            },
+            SyntaxError::Warning(w) => match w {
+                Warning::UnnecessarySemicolon => "W0001",
+                Warning::UnusedVariable(_) => "W0002",
+                Warning::UnreachableCode => "W0003",
+                Warning::Shadowing(_) => "W0004",
+            },
+            SyntaxError::Unknown => "E0000",
+        }
+    }
+
     pub fn help(&self) -> &str {
         match self {
-            SyntaxError::Lex(_) => {
-                "lexer errors are only caused by things that would cause issues in tokenization."
-            }
+            SyntaxError::Lex(e) => match e {
+                LexError::UnterminatedString(_) => "close the string with a matching \" on the same line",
+                LexError::UnterminatedChar(_) => {
+                    "chars hold exactly one character (or one escape) between single quotes"
+                }
+                LexError::UnknownToken(_) => {
+                    "remove it, or put it inside a string or comment if you meant it literally"
+                }
+            },
             SyntaxError::Parse(e) => match e {
-                ParseError::MissingExpected(msg) => {
-                    if msg.starts_with("expected type") {
-                        "either omit the colon, or specify a type (if it's a decl without a right hand side, you MUST specify type)"
-                    } else if msg.starts_with("let must have") {
-                        "if you want to discard the value, use _, otherwise attach a name"
-                    } else if msg.starts_with("type cannot be") {
-                        "either declare the type beforehand, or add a right hand side and let the compiler infer it."
-                    } else if msg.starts_with("all statements must") {
-                        "either stick them on seperate lines, or seperate them using a semicolon (bad practice, SHAME!)"
-                    } else if msg.starts_with("expected expression") {
-                        "the right hand of an equals sign cannot be blank"
-                    }
-                    // else if msg.starts_with("message start") {
-                    //     "the right hand of an equals sign cannot be blank"
-                    // }
-                    else {
-                        "Unknown"
-                    }
-                }
-
-                ParseError::ConstDisallowed(msg) => {
-                    if msg.ends_with("mutable") {
-                        "either remove the mutable tag, or denote it static (placing it in a constant memory location)"
-                    } else if msg.ends_with("static") {
-                        "remove either const or static. const is a fixed constant, whereas static is constant memory location. constant handles both"
-                    } else {
-                        "Unknown"
-                    }
+                ParseError::MissingName => {
+                    "if you want to discard the value, use _, otherwise attach a name"
+                }
+                ParseError::MissingType => {
+                    "either omit the colon, or specify a type (if it's a decl without a right hand side, you MUST specify type)"
+                }
+                ParseError::CannotInfer => {
+                    "either declare the type beforehand, or add a right hand side and let the compiler infer it."
+                }
+                ParseError::MissingValue => "the right hand of an equals sign cannot be blank",
+                ParseError::MissingTerminator => {
+                    "either stick them on seperate lines, or seperate them using a semicolon (bad practice, SHAME!)"
+                }
+                ParseError::ConstMutable => {
+                    "either remove the mutable tag, or denote it static (placing it in a constant memory location)"
+                }
+                ParseError::ConstStatic => {
+                    "remove either const or static. const is a fixed constant, whereas static is constant memory location. constant handles both"
+                }
+                ParseError::UnexpectedToken(_) => {
+                    "this can't start an expression or statement here, check for a stray symbol or a missing operand"
+                }
+                ParseError::UnexpectedEof => "the file ended in the middle of an expression, finish it off",
+                ParseError::MissingOperand => "an operator needs something on its right hand side",
+                ParseError::MissingField => "fields are accessed by name, like a.b",
+                ParseError::UnclosedCall => {
+                    "seperate call arguments with commas, and close the call with ')'"
+                }
+                ParseError::UnclosedParen | ParseError::UnclosedIndex => {
+                    "every opening bracket needs a matching closing one"
+                }
+                ParseError::InvalidAssignment => {
+                    "the left hand side of '=' has to be a name, a field (a.b) or an index (a[i])"
+                }
+                ParseError::Unsupported(_) => {
+                    "this part of the language hasn't made it into the parser yet"
+                }
+                ParseError::MissingModule => "module paths are names seperated by ::, like `import std::io`",
+                ParseError::MissingImport => "list what to bring in, like `from std::io import File, open`",
+            },
+            SyntaxError::Sema(e) => match e {
+                SemaError::DuplicateDeclaration(_) => {
+                    "const and static names are fixed, so each one can only be declared once per scope"
+                }
+                SemaError::AssignToImmutable(_) => {
+                    "declare it with `let mutable` if it needs to change"
+                }
+                SemaError::UnresolvedName(_) => {
+                    "names have to be declared with `let` before they're used, check the spelling"
+                }
+                SemaError::UnknownType(_) => {
+                    "the builtin types are i8 to i64, u8 to u64, f32, f64, bool, char and str"
+                }
+                SemaError::UnresolvedModule(_) => {
+                    "a::b is src/a/b.stick, or a dependency named a from Stick.toml. check the spelling"
+                }
+                SemaError::UnresolvedImport(_) => "only top level declarations can be imported, check the spelling",
+            },
+            SyntaxError::Manifest(e) => match e {
+                ManifestError::Malformed => "each line is a [section] header, a key = value pair, a # comment or blank",
+                ManifestError::InvalidValue(_) => {
+                    "values are \"strings\", numbers, true/false, [arrays] or { inline = \"tables\" }"
+                }
+                ManifestError::UnknownKey(_) => "`stick explain E0202` lists every section and key",
+                ManifestError::DuplicateKey(_) => "remove one of them",
+                ManifestError::MissingKey(_) => "every Stick.toml needs at least a [package] with a name",
+                ManifestError::WrongType(_) => "`stick explain E0205` lists what each key expects",
+                ManifestError::MissingPath(_) => "paths are relative to the folder Stick.toml is in",
+            },
+            SyntaxError::Warning(w) => match w {
+                Warning::UnnecessarySemicolon => {
+                    "semicolons are only needed to seperate statements on the same line (and even then, SHAME!)"
+                }
+                Warning::UnusedVariable(_) => {
+                    "remove it, or prefix the name with an underscore if that's intentional"
+                }
+                Warning::UnreachableCode => {
+                    "nothing after a return, break or continue in the same block will ever run"
+                }
+                Warning::Shadowing(_) => {
+                    "rename one of them, or make the first one mutable and reassign it instead"
                 }
             },
             SyntaxError::Unknown => "Only god can save you (or reading the docs lmao.)",
@@ -74,44 +320,134 @@ impl<'src> SyntaxError<'src> {
     }
 }
 
-// so much fucking cleaner saves me a lot of pain
-impl<'a, 'src> Display for Diagnostic<'a, 'src> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+// ariadne wants its own copy of every file a report touches, this just borrows them from wherever they live
+struct Files<'f> {
+    lookup: &'f dyn Fn(FileId) -> &'f SourceFile,
+    loaded: Vec<(FileId, Source<&'f str>)>,
+}
+
+impl<'f> Files<'f> {
+    fn load(&mut self, file: FileId) {
+        if !self.loaded.iter().any(|(id, _)| *id == file) {
+            self.loaded.push((file, Source::from((self.lookup)(file).src.as_str())));
+        }
+    }
+}
+
+impl<'f> Cache<FileId> for Files<'f> {
+    type Storage = &'f str;
+
+    fn fetch(&mut self, id: &FileId) -> std::result::Result<&Source<&'f str>, impl fmt::Debug> {
+        match self.loaded.iter().find(|(file, _)| file == id) {
+            Some((_, src)) => Ok(src),
+            None => Err(format!("no source loaded for {id:?}")),
+        }
+    }
+
+    fn display<'b>(&self, id: &'b FileId) -> Option<impl Display + 'b> {
+        Some((self.lookup)(*id).path.clone())
+    }
+}
+
+// ariadne spans are a file and a range
+fn at(span: Span) -> (FileId, Range<usize>) {
+    (span.file, span.range())
+}
+
+impl Diagnostic {
+    /// the full report, ready for a terminal. needs the SourceMap every file it points into came from
+    pub fn render(&self, sources: &SourceMap) -> String {
+        self.render_with(&|id| sources.get(id))
+    }
+
+    /// same, for one that only points into the file it's in (anything out of the lexer or parser)
+    pub fn render_in(&self, file: &SourceFile) -> String {
+        self.render_with(&|_| file)
+    }
+
+    fn render_with<'f>(&self, lookup: &'f dyn Fn(FileId) -> &'f SourceFile) -> String {
         let mut buf: Vec<u8> = Vec::new();
 
+        // points to what's fucked up
+        let primary: &str = self.primary.as_deref().unwrap_or(match self.severity {
+            Severity::Error => "error here",
+            Severity::Warning => "warning here",
+            Severity::Note | Severity::Help => "here",
+        });
+
         // main report with a short, human-friendly header
         let name: &str = self.err.name();
-        Report::build(
-            ReportKind::Custom(name, Color::Red),
-            self.path,
-            self.span.start,
-        )
-        .with_message(&self.err)
-        // points to what's fucked up
-        .with_label(Label::new((self.path, self.span.clone())).with_message("error here"))
-        // lexer help (doing different display in the parser, as i will need notes)
-        .with_help(self.err.help()) // short hint
-        .finish()
-        .write((self.path, Source::from(self.src)), &mut buf)
-        .unwrap();
+        let mut report = Report::build(ReportKind::Custom(name, self.severity.color()), at(self.span))
+            .with_code(self.err.code())
+            .with_message(&self.err)
+            .with_label(
+                ariadne::Label::new(at(self.span))
+                    .with_message(primary)
+                    .with_color(self.severity.color()),
+            );
+
+        // anything related, possibly in other files
+        for label in &self.labels {
+            report.add_label(
+                ariadne::Label::new(at(label.span))
+                    .with_message(&label.message)
+                    .with_color(Color::Blue),
+            );
+        }
+
+        // fixes point right at what they'd change
+        for fix in &self.fixes {
+            report.add_label(
+                ariadne::Label::new(at(fix.span))
+                    .with_message(&fix.message)
+                    .with_color(Color::Green),
+            );
+        }
+
+        // short hint first, then anything specific to this one
+        let auto: Option<&str> = self
+            .fixes
+            .iter()
+            .any(|fix| fix.safe)
+            .then_some("`stick fix <file>` can apply this for you");
+        report.with_helps(
+            std::iter::once(self.err.help())
+                .chain(self.helps.iter().map(String::as_str))
+                .chain(auto),
+        );
+        report.with_notes(&self.notes);
+
+        // every file any label touches has to be in the cache
+        let mut files: Files<'_> = Files {
+            lookup,
+            loaded: Vec::new(),
+        };
+        files.load(self.file());
+        for label in &self.labels {
+            files.load(label.span.file);
+        }
+        for fix in &self.fixes {
+            files.load(fix.span.file);
+        }
+        report.finish().write(&mut files, &mut buf).unwrap();
 
         // moo
-        write!(f, "{}", String::from_utf8_lossy(&buf))
+        String::from_utf8_lossy(&buf).into_owned()
     }
 }
 
 // dump any found errors
-pub fn dump(errors: &[Diagnostic<'_, '_>], path: &str) -> io::Result<()> {
+pub fn dump(sources: &SourceMap, errors: &[Diagnostic], path: &str) -> io::Result<()> {
     let file: File = File::create(path)?;
     let mut writer: BufWriter<File> = BufWriter::new(file);
 
     for diag in errors {
         // strip ANSI escape sequences
-        let stripped: Vec<u8> = strip(diag.to_string().as_bytes());
+        let stripped: Vec<u8> = strip(diag.render(sources).as_bytes());
 
         // write clean UTF-8 (lossy is fine for logs)
         writeln!(writer, "{}", String::from_utf8_lossy(&stripped))?;
     }
 
     writer.flush()
-}
\ No newline at end of file
+}
@@ -2,7 +2,7 @@ use strum_macros::AsRefStr;
 
 use std::fmt::{Display, Formatter, Result};
 
-use crate::lexer::Token;
+use crate::{intern::Symbol, lexer::Token};
 use logos::Lexer;
 
 /// i don't need this schlanging out but i don't wanna type it 20 times
@@ -10,67 +10,174 @@ use logos::Lexer;
 macro_rules! usage {
     () => {
         eprintln!("usage:");
-        eprintln!("cargo run (optional: --release) <file>\n");
+        eprintln!("cargo run (optional: --release) [command] [file] [flags]\n");
+        eprintln!("commands:");
+        eprintln!("check [file]     = lex, parse and resolve the file and report what's wrong (what you get with no command).");
+        eprintln!("                   with no file (or a folder), checks the whole project the nearest Stick.toml describes");
+        eprintln!("build [file]     = check, then build (no backend yet, so --emit is the only thing that makes anything)");
+        eprintln!("run [file]       = build, then run (same deal, no backend yet)");
+        eprintln!("new <name>       = start a project: <name>/Stick.toml, <name>/src/main.stick and a .gitignore");
+        eprintln!("fmt <file>       = reformat the file in place (--check to only report whether it would change)");
+        eprintln!("fix <file>       = apply every safe fix-it to the file in place");
+        eprintln!("doc [file]       = write markdown api docs from the /// comments, a page per module of the project at");
+        eprintln!("                   doc/<module path>.md (or just the file's page). -o for another folder");
+        eprintln!("repl             = check lines one at a time and print them back the way they parsed");
+        eprintln!("lsp              = run the language server over stdin/stdout (point your editor at this)");
+        eprintln!("explain <code>   = print the long explanation for an error code (like E0012)\n");
         eprintln!("flags:");
-        eprintln!("-d | --debug     = debug mode on, prints lexer and parser outputs, as well as time and some performance stats.");
+        eprintln!("-d | --debug     = debug mode on, prints lexer and parser outputs, as well as time and some performance stats");
+        eprintln!("                   (and how much of a project came from the build cache in target/).");
         eprintln!("-ff | --fastfail = fail immediately on one syntax error instead of warning you of others.");
-        exit(2);
+        eprintln!("-q | --quiet     = don't print the lexing/parsing stats lines.");
+        eprintln!("--step           = pause between compiler stages until enter is pressed.");
+        eprintln!("--release        = build a project with its release profile instead of debug.");
+        eprintln!("-j | --jobs <n>  = how many modules of a project get compiled at once (one per core by default).");
+        eprintln!("-w | --watch     = (check only) check again every time the file or any module of the project changes.");
+        eprintln!("-o | --out <path> = where output goes (--emit, doc).");
+        eprintln!("--emit=<stage>[=<path>] = write one stage's output (to stdout, or path) and stop there.");
+        eprintln!("                  stages: tokens, ast, ast-json, typed-ast, ir, bytecode");
+        eprintln!("--error-format=<human|json|sarif> = how errors are printed. json is one object per line, sarif is one log for code scanning.");
+        eprintln!("-W | -A | -D <lint> = warn on, allow or deny a lint (or `warnings` for all of them).");
+        eprintln!("                      lints: unnecessary-semicolon, unused-variable, shadowing, unreachable-code");
+        eprintln!("                      (unreachable-code isn't active until return/break/continue parse)\n");
+        eprintln!("exit codes: 1 = errors in the code, 2 = usage (or not implemented yet), 3 = couldn't read/write a file, 4 = compiler bug");
+        std::process::exit($crate::cli::EXIT_USAGE);
     };
 
     // if something provided print it first (this macro allows for any formatting inside)
     ($($msg:tt)+) => {{
         eprintln!($($msg)*);
-        usage!();
+        $crate::usage!();
     }};
 }
 
 /// a generic error for anything that may happen during lexing.
 #[derive(Debug, PartialEq, Clone, AsRefStr)]
-pub enum LexError<'src> {
-    UnterminatedString(&'src str),
-    UnterminatedChar(&'src str),
-    UnknownToken(&'src str),
+pub enum LexError {
+    UnterminatedString(String),
+    UnterminatedChar(String),
+    UnknownToken(String),
 }
 
 /// a generic error for anything that may happen during parsing.
 #[derive(Debug, PartialEq, Clone, AsRefStr)]
-pub enum ParseError<'src> {
-    // an expected token is missing
-    MissingExpected(&'src str),
+pub enum ParseError {
+    // let declarations
+    MissingName,
+    MissingType,
+    CannotInfer,
+    MissingValue,
 
-    // const is not allowed in tandem w this variable
-    ConstDisallowed(&'src str),
+    // statements have to end on a newline or semicolon
+    MissingTerminator,
+
+    // const is not allowed in tandem w these
+    ConstMutable,
+    ConstStatic,
+
+    // expressions (UnexpectedToken holds the token itself)
+    UnexpectedToken(String),
+    UnexpectedEof,
+    MissingOperand,
+    MissingField,
+
+    // unclosed brackets
+    UnclosedCall,
+    UnclosedParen,
+    UnclosedIndex,
+
+    // left hand side of an assignment isn't something you can assign to
+    InvalidAssignment,
+
+    // valid syntax that the parser just can't handle yet (holds what it is)
+    Unsupported(&'static str),
+
+    // imports (`import a::b`, `from a::b import c`)
+    MissingModule,
+    MissingImport,
+}
+
+/// a generic error for anything that may happen during semantic analysis.
+#[derive(Debug, PartialEq, Clone, AsRefStr)]
+pub enum SemaError {
+    // a const or static name declared twice in the same scope (holds the name)
+    DuplicateDeclaration(Symbol),
+
+    // assigning to something that wasn't declared mutable (holds the name)
+    AssignToImmutable(Symbol),
+
+    // a name or type nothing declared (holds the name)
+    UnresolvedName(Symbol),
+    UnknownType(Symbol),
+
+    // an import of a module that doesn't exist, or of a name the module doesn't have (holds the path/name)
+    UnresolvedModule(String),
+    UnresolvedImport(Symbol),
+}
+
+/// anything wrong with a Stick.toml
+#[derive(Debug, PartialEq, Clone, AsRefStr)]
+pub enum ManifestError {
+    // a line that isn't `[section]`, `key = value`, a comment or blank
+    Malformed,
+
+    // the right hand side of an `=` isn't a value we understand (holds it)
+    InvalidValue(String),
+
+    // a section or key that doesn't mean anything, the same key twice, or a required one left out (holds the key)
+    UnknownKey(String),
+    DuplicateKey(String),
+    MissingKey(String),
+
+    // a key with the wrong kind of value (holds the key)
+    WrongType(String),
+
+    // a path that doesn't exist on disk (holds the path)
+    MissingPath(String),
+}
+
+/// everything the compiler can warn about. each one maps to a lint category that can be allowed/denied
+#[derive(Debug, PartialEq, Clone, AsRefStr)]
+pub enum Warning {
+    // a semicolon with nothing after it on the same line
+    UnnecessarySemicolon,
+
+    // declared but never read (holds the name)
+    UnusedVariable(Symbol),
+
+    // anything after a return/break/continue in the same block
+    UnreachableCode,
+
+    // redeclaring a name that's already in scope (holds the name)
+    Shadowing(Symbol),
 }
 
 /// unified place to hold any error that may happen during compile time
 #[derive(Debug, PartialEq, Clone, Default, AsRefStr)]
-pub enum SyntaxError<'src> {
-    Lex(LexError<'src>),
-    Parse(ParseError<'src>),
+pub enum SyntaxError {
+    Lex(LexError),
+    Parse(ParseError),
+    Sema(SemaError),
+    Manifest(ManifestError),
+    Warning(Warning),
     
     #[default]
     Unknown,
 }
 
 // TODO: fix this nesting (and some others, GOD...)
-impl Display for SyntaxError<'_> {
+impl Display for SyntaxError {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
             // lex errors
             SyntaxError::Lex(le) => {
                 use LexError::*;
                 match le {
-                    UnterminatedString(s) => write!(
-                        f, "\x1b[1mUnterminatedString:\x1b[22m Strings must be properly terminated, {s} is missing termination"
-                    ),
-
-                    UnterminatedChar(s) => write!(
-                        f, "\x1b[1mUnterminatedChar:\x1b[22m Chars must be properly terminated, {s} is missing termination"
-                    ),
-
-                    UnknownToken(s) => write!(
-                        f, "\x1b[1mUnknownToken:\x1b[22m The character '{s}' is not in the grammar for this language."
-                    ),
+                    UnterminatedString(s) => {
+                        write!(f, "strings must be properly terminated, {s} is missing termination")
+                    }
+                    UnterminatedChar(s) => write!(f, "chars must be properly terminated, {s} is missing termination"),
+                    UnknownToken(s) => write!(f, "the character '{s}' is not in the grammar for this language"),
                 }
             }
 
@@ -78,8 +185,62 @@ impl Display for SyntaxError<'_> {
             SyntaxError::Parse(pe) => {
                 use ParseError::*;
                 match &pe {
-                    MissingExpected(s) => write!(f, "missing a value where expected, {s}"),
-                    ConstDisallowed(s) => write!(f, "const cannot be used with some modifiers: {s}"),
+                    MissingName => write!(f, "let must have an identifier afterwards"),
+                    MissingType => write!(f, "expected type name after ':'"),
+                    CannotInfer => write!(f, "type cannot be inferred without a right hand side"),
+                    MissingValue => write!(f, "expected expression after '='"),
+                    MissingTerminator => write!(f, "all statements must be followed by either a newline or semicolon"),
+                    ConstMutable => write!(f, "const cannot be used in tandem with mutable"),
+                    ConstStatic => write!(f, "const cannot be used in tandem with static"),
+                    UnexpectedToken(s) => write!(f, "unexpected token '{s}'"),
+                    UnexpectedEof => write!(f, "expected expression, found end of file"),
+                    MissingOperand => write!(f, "expected expression before the end of the statement"),
+                    MissingField => write!(f, "expected field name after '.'"),
+                    UnclosedCall => write!(f, "expected ',' or ')' after call argument"),
+                    UnclosedParen => write!(f, "expected ')' to close the parenthesis"),
+                    UnclosedIndex => write!(f, "expected ']' to close the index"),
+                    InvalidAssignment => write!(f, "invalid assignment target, only variables, fields and indexes can be assigned to"),
+                    Unsupported(s) => write!(f, "{s} are not supported yet"),
+                    MissingModule => write!(f, "expected a module path like a::b after import"),
+                    MissingImport => write!(f, "expected `import` and the names to bring in after `from <module>`"),
+                }
+            }
+
+            // sema errors
+            SyntaxError::Sema(se) => {
+                use SemaError::*;
+                match se {
+                    DuplicateDeclaration(s) => write!(f, "'{s}' is already declared in this scope"),
+                    AssignToImmutable(s) => write!(f, "cannot assign to immutable variable '{s}'"),
+                    UnresolvedName(s) => write!(f, "cannot find '{s}' in this scope"),
+                    UnknownType(s) => write!(f, "unknown type '{s}'"),
+                    UnresolvedModule(s) => write!(f, "cannot find module '{s}'"),
+                    UnresolvedImport(s) => write!(f, "cannot find '{s}' in the imported module"),
+                }
+            }
+
+            // manifest errors
+            SyntaxError::Manifest(me) => {
+                use ManifestError::*;
+                match me {
+                    Malformed => write!(f, "expected `[section]` or `key = value`"),
+                    InvalidValue(s) => write!(f, "'{s}' isn't a value (strings need quotes)"),
+                    UnknownKey(s) => write!(f, "unknown key '{s}'"),
+                    DuplicateKey(s) => write!(f, "'{s}' is set more than once"),
+                    MissingKey(s) => write!(f, "missing required key '{s}'"),
+                    WrongType(s) => write!(f, "'{s}' has the wrong type of value"),
+                    MissingPath(s) => write!(f, "'{s}' doesn't exist"),
+                }
+            }
+
+            // warnings
+            SyntaxError::Warning(w) => {
+                use Warning::*;
+                match w {
+                    UnnecessarySemicolon => write!(f, "unnecessary semicolon"),
+                    UnusedVariable(s) => write!(f, "variable '{s}' is declared but never used"),
+                    UnreachableCode => write!(f, "unreachable code"),
+                    Shadowing(s) => write!(f, "'{s}' shadows an earlier declaration"),
                 }
             }
 
@@ -99,12 +260,12 @@ impl Display for SyntaxError<'_> {
 ///
 /// # Returns
 /// an option from the SyntaxError enum if matched, or unknown by default
-pub fn lex_err<'src>(lex: &mut Lexer<'src, Token<'src>>) -> SyntaxError<'src> {
+pub fn lex_err<'src>(lex: &mut Lexer<'src, Token<'src>>) -> SyntaxError {
     let slice: &str = lex.slice();
     match slice.as_bytes().first() {
-        Some(b'"') => SyntaxError::Lex(LexError::UnterminatedString(slice)),
-        Some(b'\'') => SyntaxError::Lex(LexError::UnterminatedChar(slice)),
-        Some(_) => SyntaxError::Lex(LexError::UnknownToken(slice)),
+        Some(b'"') => SyntaxError::Lex(LexError::UnterminatedString(slice.to_string())),
+        Some(b'\'') => SyntaxError::Lex(LexError::UnterminatedChar(slice.to_string())),
+        Some(_) => SyntaxError::Lex(LexError::UnknownToken(slice.to_string())),
 
         // catch all in case none matched
         None => SyntaxError::Unknown,
@@ -0,0 +1,529 @@
+/// accept e12, 12 and E0012 as the same code, typing is hard
+pub fn normalize(code: &str) -> String {
+    let code: String = code.trim().to_ascii_uppercase();
+    let (prefix, digits) = match code.strip_prefix('W') {
+        Some(digits) => ('W', digits),
+        None => ('E', code.strip_prefix('E').unwrap_or(&code)),
+    };
+    format!("{prefix}{digits:0>4}")
+}
+
+/// the long form of every error code, printed by `stick explain <code>`.
+/// each one is a short description, then something that triggers it and how to fix it
+pub fn explain(code: &str) -> Option<&'static str> {
+    let text: &'static str = match normalize(code).as_str() {
+        "E0000" => {
+            r#"an unknown error. something went wrong that the compiler doesn't have a name for yet.
+
+if you hit this, it's a bug in the compiler, not your code. please report it with the file that caused it."#
+        }
+
+        "E0001" => {
+            r#"a string literal was never closed.
+
+strings start and end with a double quote, and can't span multiple lines.
+
+bad:
+    let name = "stick
+
+good:
+    let name = "stick""#
+        }
+
+        "E0002" => {
+            r#"a char literal was never closed, or holds more than one character.
+
+chars are exactly one character (or one escape like '\n') between single quotes.
+
+bad:
+    let c = 'ab'
+    let d = 'a
+
+good:
+    let c = 'a'
+    let d = '\n'"#
+        }
+
+        "E0003" => {
+            r#"a character that isn't part of the language showed up outside a string or comment.
+
+bad:
+    let price = 5$
+
+good:
+    let price = 5
+    let label = "5$""#
+        }
+
+        "E0010" => {
+            r#"a `let` has no name after it (or after its modifiers).
+
+bad:
+    let = 5
+    let mutable: i32 = 5
+
+good:
+    let x = 5
+    let mutable count: i32 = 5"#
+        }
+
+        "E0011" => {
+            r#"a colon after a declaration's name wasn't followed by a type.
+
+either write the type, or drop the colon and let the compiler infer it.
+
+bad:
+    let testing: = 5
+
+good:
+    let testing: i32 = 5
+    let testing = 5"#
+        }
+
+        "E0012" => {
+            r#"a declaration has no type and no value, so there's nothing to infer its type from.
+
+declarations without a right hand side MUST say what type they are. `_` asks for inference,
+so it doesn't count either.
+
+bad:
+    let inferred
+    let const thing: _
+
+good:
+    let declared: i32
+    let inferred = 5"#
+        }
+
+        "E0013" => {
+            r#"an `=` in a declaration had nothing on its right hand side.
+
+bad:
+    let testing: i32 =
+
+good:
+    let testing: i32 = 5
+    let testing: i32"#
+        }
+
+        "E0014" => {
+            r#"two statements were written on the same line without anything seperating them.
+
+every statement ends at a newline, or (if you really have to) a semicolon.
+
+bad:
+    let a = 1 let b = 2
+
+good:
+    let a = 1
+    let b = 2"#
+        }
+
+        "E0015" => {
+            r#"`const` and `mutable` were used on the same declaration.
+
+a const can never change, so it can't be mutable. if you want a mutable value at a fixed
+location in memory, use `static mutable` instead.
+
+bad:
+    let const mutable failure = 5
+
+good:
+    let const success = 5
+    let static mutable counter = 5"#
+        }
+
+        "E0016" => {
+            r#"`const` and `static` were used on the same declaration.
+
+const already means a fixed value in a fixed location, so static adds nothing. pick one.
+
+bad:
+    let const static failure = 6
+
+good:
+    let const fixed = 6
+    let static location = 6"#
+        }
+
+        "E0017" => {
+            r#"a token showed up somewhere it can't be used, like a stray bracket or an operator with
+nothing on its left.
+
+bad:
+    let x = ]
+    let y = * 2
+
+good:
+    let x = arr[0]
+    let y = 3 * 2"#
+        }
+
+        "E0018" => {
+            r#"the file ended in the middle of an expression.
+
+bad:
+    let total = price *
+
+good:
+    let total = price * count"#
+        }
+
+        "E0019" => {
+            r#"an operator had nothing on its right hand side before the statement ended.
+
+bad:
+    let sum = a +
+
+good:
+    let sum = a + b"#
+        }
+
+        "E0020" => {
+            r#"a `.` wasn't followed by a field name.
+
+bad:
+    let x = point.
+
+good:
+    let x = point.x"#
+        }
+
+        "E0021" => {
+            r#"a call's arguments weren't seperated by commas, or the call was never closed.
+
+bad:
+    print(a b)
+    print(a, b
+
+good:
+    print(a, b)"#
+        }
+
+        "E0022" => {
+            r#"an opening parenthesis was never closed.
+
+bad:
+    let x = (1 + 2
+
+good:
+    let x = (1 + 2)"#
+        }
+
+        "E0023" => {
+            r#"an index or slice was never closed with `]`.
+
+bad:
+    let first = items[0
+    let rest = items[1..
+
+good:
+    let first = items[0]
+    let rest = items[1..]"#
+        }
+
+        "E0024" => {
+            r#"something that can't be assigned to was on the left hand side of an assignment.
+
+only variables, fields and indexes can be assigned to.
+
+bad:
+    f(x) = 5
+    1 + 2 = x
+
+good:
+    x = 5
+    point.x = 5
+    items[0] = 5"#
+        }
+
+        "E0025" => {
+            r#"this is valid stick, the parser just doesn't handle it yet.
+
+it'll work once it's implemented. until then, there's usually a way to write the same thing
+with plain declarations and expressions."#
+        }
+
+        "E0026" => {
+            r#"an import is missing the module it imports.
+
+module paths are names seperated by ::. a::b is the file src/a/b.stick, or b inside a dependency called a.
+
+bad:
+    import
+    from import x
+
+good:
+    import util
+    from util::math import square"#
+        }
+
+        "E0027" => {
+            r#"a `from` import doesn't say what to bring in.
+
+`from <module>` has to be followed by `import` and the names you want, seperated by commas.
+to just depend on a module, use a plain `import <module>` instead.
+
+bad:
+    from util::math
+    from util::math import
+
+good:
+    from util::math import square, cube
+    import util::math"#
+        }
+
+        "E0100" => {
+            r#"a const or static name was declared twice in the same scope.
+
+consts and statics live at a fixed location, so there can only be one of each name per scope.
+regular declarations are allowed to shadow (with a warning).
+
+bad:
+    let static total = 1
+    let static total = 2
+
+good:
+    let static mutable total = 1
+    total = 2"#
+        }
+
+        "E0101" => {
+            r#"a value that wasn't declared mutable was assigned to after it got its value.
+
+declarations without a value can be assigned exactly once (that's their initialization),
+everything else needs `mutable` to change.
+
+bad:
+    let x = 1
+    x = 2
+
+good:
+    let mutable x = 1
+    x = 2
+
+    let y: i32
+    y = 2"#
+        }
+
+        "E0102" => {
+            r#"a name was used that nothing in scope declares.
+
+names only exist from their `let` onwards, and only inside the block they were declared in.
+this also catches misspelled keywords, since those lex as plain names.
+
+bad:
+    let count = 1
+    cuont = 2
+    retrun count
+
+good:
+    let mutable count = 1
+    count = 2
+    return count"#
+        }
+
+        "E0103" => {
+            r#"a type annotation names a type that doesn't exist.
+
+the builtin types are i8, u8, i16, u16, i32, u32, i64, u64, f32, f64, bool, char, str and ().
+user defined types aren't in the language yet, so any other name is an error for now.
+
+bad:
+    let x: i23 = 5
+    let s: Strng = "hi"
+
+good:
+    let x: i32 = 5
+    let s: str = "hi""#
+        }
+
+        "E0104" => {
+            r#"an import names a module that doesn't exist.
+
+inside a project (a folder with a Stick.toml), a::b is looked up as a/b.stick in each source folder,
+or as b inside the dependency called a. outside a project imports aren't checked at all.
+
+bad:
+    import utill
+
+good:
+    import util"#
+        }
+
+        "E0105" => {
+            r#"a `from` import asks for a name the module doesn't declare.
+
+only top level declarations in a module can be imported.
+
+bad:
+    // util.stick has `let square = 1`
+    from util import sqaure
+
+good:
+    from util import square"#
+        }
+
+        "E0200" => {
+            r#"a line in Stick.toml isn't something the manifest understands.
+
+every line is a [section] header, a `key = value` pair, a # comment, or blank.
+arrays and inline tables have to fit on one line.
+
+bad:
+    [package
+    name "demo"
+
+good:
+    [package]
+    name = "demo""#
+        }
+
+        "E0201" => {
+            r#"a value in Stick.toml isn't valid.
+
+values are "strings", whole numbers, true or false, [arrays, of, values] or { inline = "tables" }.
+strings always need their quotes.
+
+bad:
+    name = demo
+
+good:
+    name = "demo""#
+        }
+
+        "E0202" => {
+            r#"Stick.toml has a section or key that doesn't mean anything.
+
+the sections and keys are:
+    [package]         name, version, entry, src
+    [dependencies]    <name> = { path = "..." } (or just <name> = "...")
+    [profile.<name>]  opt-level, debug
+
+bad:
+    [package]
+    nmae = "demo"
+
+good:
+    [package]
+    name = "demo""#
+        }
+
+        "E0203" => {
+            r#"a key in Stick.toml is set twice in the same section.
+
+bad:
+    [package]
+    name = "demo"
+    name = "other"
+
+good:
+    [package]
+    name = "demo""#
+        }
+
+        "E0204" => {
+            r#"Stick.toml is missing a key it needs.
+
+the only required one is the package name. everything else has a default
+(version "0.1.0", entry "src/main.stick", src ["src"]).
+
+bad:
+    [package]
+    version = "1.0.0"
+
+good:
+    [package]
+    name = "demo"
+    version = "1.0.0""#
+        }
+
+        "E0205" => {
+            r#"a key in Stick.toml has the wrong kind of value.
+
+name, version and entry are strings, src is an array of strings, dependencies are a string path
+or { path = "..." }, opt-level is a number from 0 to 3 and debug is true or false.
+
+bad:
+    src = "src"
+    opt-level = "3"
+
+good:
+    src = ["src"]
+    opt-level = 3"#
+        }
+
+        "E0206" => {
+            r#"a path in Stick.toml doesn't exist.
+
+paths are relative to the folder the Stick.toml is in. dependencies point at the folder holding
+the other package's Stick.toml.
+
+bad:
+    entry = "src/mian.stick"
+    util = { path = "../utils" }
+
+good:
+    entry = "src/main.stick"
+    util = { path = "../util" }"#
+        }
+
+        "W0001" => {
+            r#"a semicolon was used where a newline already ends the statement. (lint: unnecessary-semicolon)
+
+semicolons are only needed to put multiple statements on one line.
+
+bad:
+    let x = 5;
+
+good:
+    let x = 5"#
+        }
+
+        "W0002" => {
+            r#"a variable was declared but never read. (lint: unused-variable)
+
+if that's on purpose, start the name with an underscore.
+
+bad:
+    let unused = 5
+
+good:
+    let _unused = 5"#
+        }
+
+        "W0003" => {
+            r#"code comes after a return, break or continue in the same block, so it can never run.
+(lint: unreachable-code)
+
+not active yet: the parser doesn't accept return, break or continue so far, so nothing can set this off.
+-W/-A/-D unreachable-code are still accepted, so a build that sets it keeps working once they parse.
+
+bad:
+    return x
+    let y = 5
+
+good:
+    let y = 5
+    return x"#
+        }
+
+        "W0004" => {
+            r#"a declaration reuses a name that's already in scope, hiding the first one. (lint: shadowing)
+
+anything after the new declaration can't see the old value anymore.
+
+bad:
+    let total = 1
+    let total = total + 1
+
+good:
+    let mutable total = 1
+    total = total + 1"#
+        }
+
+        _ => return None,
+    };
+
+    Some(text)
+}
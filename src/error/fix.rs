@@ -0,0 +1,138 @@
+use super::{Diagnostic, diagnostic::Fix};
+use crate::source::SourceFile;
+
+/// every safe fix across a set of diagnostics
+pub fn safe_fixes(diagnostics: &[Diagnostic]) -> Vec<&Fix> {
+    diagnostics.iter().flat_map(|d| &d.fixes).filter(|fix| fix.safe).collect()
+}
+
+/// applies as many fixes as it can to a file, returning its new source and how many went in.
+/// fixes for other files are left alone, and ones that overlap a fix that's already been taken get skipped
+/// (the next pass picks them up if they still apply)
+pub fn apply_fixes(file: &SourceFile, fixes: &[&Fix]) -> (String, usize) {
+    let src: &str = &file.src;
+    let mut fixes: Vec<&Fix> = fixes.iter().copied().filter(|fix| fix.span.file == file.id).collect();
+    fixes.sort_by_key(|fix| (fix.span.start, fix.span.end));
+
+    // two inserts at the same spot count as overlapping too, no way to know which goes first
+    let mut taken: Vec<&Fix> = Vec::new();
+    for fix in fixes {
+        let clashes: bool = taken.last().is_some_and(|last| {
+            fix.span.start < last.span.end || fix.span.start == last.span.start
+        });
+        // a span off the end or through the middle of a char can't be right, so it's not worth trying
+        let fits: bool = fix.span.start <= fix.span.end && src.get(fix.span.range()).is_some();
+        if !clashes && fits {
+            taken.push(fix);
+        }
+    }
+
+    // back to front so earlier spans stay valid
+    let mut out: String = src.to_string();
+    for fix in taken.iter().rev() {
+        out.replace_range(fix.span.range(), &fix.replacement);
+    }
+    (out, taken.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{FileId, SourceMap, Span};
+    use std::ops::Range;
+
+    // everything here is in the first file of the map `apply` makes
+    fn fix(span: Range<usize>, replacement: &str) -> Fix {
+        Fix {
+            span: Span::of(FileId::default(), span),
+            replacement: replacement.to_string(),
+            message: String::new(),
+            safe: true,
+        }
+    }
+
+    fn apply(src: &str, fixes: &[Fix]) -> (String, usize) {
+        let mut sources: SourceMap = SourceMap::new();
+        let file: FileId = sources.add("a.stick", src);
+        apply_fixes(sources.get(file), &fixes.iter().collect::<Vec<&Fix>>())
+    }
+
+    #[test]
+    fn replace_insert_delete() {
+        let src: &str = "let x = 1;\nlet y = 2";
+        let fixes: [Fix; 3] = [fix(9..10, ""), fix(4..5, "z"), fix(11..11, "// two\n")];
+        assert_eq!(apply(src, &fixes), ("let z = 1\n// two\nlet y = 2".to_string(), 3));
+    }
+
+    #[test]
+    fn nothing_to_do() {
+        assert_eq!(apply("let x = 1", &[]), ("let x = 1".to_string(), 0));
+    }
+
+    #[test]
+    fn order_given_doesnt_matter() {
+        let forward: [Fix; 2] = [fix(0..1, "A"), fix(2..3, "C")];
+        let backward: [Fix; 2] = [fix(2..3, "C"), fix(0..1, "A")];
+        assert_eq!(apply("abc", &forward), ("AbC".to_string(), 2));
+        assert_eq!(apply("abc", &backward), ("AbC".to_string(), 2));
+    }
+
+    #[test]
+    fn overlapping_fixes_keep_the_first() {
+        // whichever starts first goes in, the one it overlaps waits for the next pass
+        let fixes: [Fix; 3] = [fix(2..6, "X"), fix(0..3, "Y"), fix(8..9, "Z")];
+        assert_eq!(apply("0123456789", &fixes), ("Y34567Z9".to_string(), 2));
+
+        // one inside another
+        let fixes: [Fix; 2] = [fix(0..10, "all"), fix(3..4, "x")];
+        assert_eq!(apply("0123456789", &fixes), ("all".to_string(), 1));
+    }
+
+    #[test]
+    fn adjacent_fixes_both_go_in() {
+        let fixes: [Fix; 3] = [fix(0..2, "ab"), fix(2..4, "cd"), fix(4..4, "!")];
+        assert_eq!(apply("0123", &fixes), ("abcd!".to_string(), 3));
+    }
+
+    #[test]
+    fn two_inserts_at_one_spot_clash() {
+        let fixes: [Fix; 2] = [fix(1..1, "x"), fix(1..1, "y")];
+        assert_eq!(apply("ab", &fixes), ("axb".to_string(), 1));
+
+        // an insert where a replacement starts clashes too
+        let fixes: [Fix; 2] = [fix(1..1, "x"), fix(1..2, "B")];
+        assert_eq!(apply("ab", &fixes), ("axb".to_string(), 1));
+    }
+
+    #[test]
+    fn bad_spans_are_skipped() {
+        // off the end, backwards, and through the middle of é
+        let fixes: [Fix; 4] = [fix(5..9, "x"), fix(Range { start: 3, end: 1 }, "y"), fix(1..2, "z"), fix(0..1, "E")];
+        assert_eq!(apply("aé", &fixes), ("Eé".to_string(), 1));
+    }
+
+    #[test]
+    fn other_files_are_left_alone() {
+        let mut sources: SourceMap = SourceMap::new();
+        let a: FileId = sources.add("a.stick", "abc");
+        let b: FileId = sources.add("b.stick", "xyz");
+        let mut theirs: Fix = fix(0..1, "X");
+        theirs.span.file = b;
+        let fixes: [Fix; 2] = [fix(1..2, "B"), theirs];
+        let fixes: Vec<&Fix> = fixes.iter().collect();
+        assert_eq!(apply_fixes(sources.get(a), &fixes), ("aBc".to_string(), 1));
+        assert_eq!(apply_fixes(sources.get(b), &fixes), ("Xyz".to_string(), 1));
+    }
+
+    #[test]
+    fn only_safe_fixes() {
+        use crate::error::{Severity, SyntaxError};
+        let mut sources: SourceMap = SourceMap::new();
+        let file: FileId = sources.add("a.stick", "");
+        let diag: Diagnostic = Diagnostic::new(Span::of(file, 0..0), SyntaxError::Unknown, Severity::Error)
+            .with_fix(Span::of(file, 0..0), "safe", "")
+            .with_suggestion(Span::of(file, 0..0), "unsafe", "");
+        let fixes: Vec<&Fix> = safe_fixes(std::slice::from_ref(&diag));
+        assert_eq!(fixes.iter().map(|f| f.replacement.as_str()).collect::<Vec<&str>>(), ["safe"]);
+    }
+}
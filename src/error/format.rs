@@ -0,0 +1,230 @@
+use super::{Diagnostic, Severity, explain};
+use crate::{
+    json::Json,
+    source::{Encoding, LineCol, SourceFile, SourceMap, Span},
+};
+
+/// how diagnostics get printed. human is the pretty ariadne output, json is one object per
+/// diagnostic per line, sarif is a single SARIF 2.1.0 log for code scanning dashboards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+    #[default]
+    Human,
+    Json,
+    Sarif,
+}
+
+impl ErrorFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "human" => Some(ErrorFormat::Human),
+            "json" => Some(ErrorFormat::Json),
+            "sarif" => Some(ErrorFormat::Sarif),
+            _ => None,
+        }
+    }
+}
+
+impl Severity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+            Severity::Help => "help",
+        }
+    }
+}
+
+// where something is, in every unit a tool might want (columns count chars)
+fn location(sources: &SourceMap, span: Span) -> Json {
+    let file: &SourceFile = sources.get(span.file);
+    let start: LineCol = file.lines().line_col(span.start, Encoding::Utf32);
+    let end: LineCol = file.lines().line_col(span.end, Encoding::Utf32);
+    Json::obj([
+        ("file", file.path.as_str().into()),
+        ("byte_start", span.start.into()),
+        ("byte_end", span.end.into()),
+        ("line_start", start.line.into()),
+        ("column_start", start.col.into()),
+        ("line_end", end.line.into()),
+        ("column_end", end.col.into()),
+    ])
+}
+
+impl Diagnostic {
+    /// everything in the report as a json object (no ansi codes anywhere)
+    pub fn to_json(&self, sources: &SourceMap) -> Json {
+        let mut labels: Vec<Json> = vec![Json::obj([
+            ("primary", true.into()),
+            ("message", self.primary.clone().into()),
+            ("location", location(sources, self.span)),
+        ])];
+        labels.extend(self.labels.iter().map(|label| {
+            Json::obj([
+                ("primary", false.into()),
+                ("message", label.message.as_str().into()),
+                ("location", location(sources, label.span)),
+            ])
+        }));
+
+        let fixes: Vec<Json> = self
+            .fixes
+            .iter()
+            .map(|fix| {
+                Json::obj([
+                    ("message", fix.message.as_str().into()),
+                    ("replacement", fix.replacement.as_str().into()),
+                    ("safe", fix.safe.into()),
+                    ("location", location(sources, fix.span)),
+                ])
+            })
+            .collect();
+
+        let mut help: Vec<&str> = vec![self.err.help()];
+        help.extend(self.helps.iter().map(String::as_str));
+
+        Json::obj([
+            ("code", self.err.code().into()),
+            ("name", self.err.name().into()),
+            ("severity", self.severity.name().into()),
+            ("message", self.message().into()),
+            ("location", location(sources, self.span)),
+            ("labels", Json::Arr(labels)),
+            ("help", help.into()),
+            ("notes", self.notes.iter().map(String::as_str).collect::<Vec<_>>().into()),
+            ("fixes", Json::Arr(fixes)),
+        ])
+    }
+}
+
+// a sarif region, lines/columns are 1 based and the end column is exclusive. sarif counts columns in utf-16
+// code units unless told otherwise, and char offsets in chars, so the offsets go out as byte ones instead
+fn sarif_location(sources: &SourceMap, span: Span, message: Option<&str>) -> Json {
+    let file: &SourceFile = sources.get(span.file);
+    let start: LineCol = file.lines().line_col(span.start, Encoding::Utf16);
+    let end: LineCol = file.lines().line_col(span.end, Encoding::Utf16);
+
+    let mut location: Vec<(&str, Json)> = vec![(
+        "physicalLocation",
+        Json::obj([
+            ("artifactLocation", Json::obj([("uri", file.path.as_str().into())])),
+            (
+                "region",
+                Json::obj([
+                    ("startLine", start.line.into()),
+                    ("startColumn", start.col.into()),
+                    ("endLine", end.line.into()),
+                    ("endColumn", end.col.into()),
+                    ("byteOffset", span.start.into()),
+                    ("byteLength", (span.end - span.start).into()),
+                ]),
+            ),
+        ]),
+    )];
+    if let Some(message) = message {
+        location.push(("message", Json::obj([("text", message.into())])));
+    }
+    Json::obj(location)
+}
+
+/// one SARIF 2.1.0 log holding every diagnostic, with a rule per error code that showed up
+pub fn sarif(sources: &SourceMap, diagnostics: &[Diagnostic]) -> Json {
+    // rules are listed once each, in the order they first show up
+    let mut codes: Vec<(&str, &str)> = Vec::new();
+    for diag in diagnostics {
+        let code: &str = diag.err.code();
+        if !codes.iter().any(|(c, _)| *c == code) {
+            codes.push((code, diag.err.name()));
+        }
+    }
+
+    let rules: Vec<Json> = codes
+        .iter()
+        .map(|(code, name)| {
+            let full: &str = explain(code).unwrap_or(name);
+            let short: &str = full.lines().next().unwrap_or(name);
+            Json::obj([
+                ("id", (*code).into()),
+                ("name", (*name).into()),
+                ("shortDescription", Json::obj([("text", short.into())])),
+                ("fullDescription", Json::obj([("text", full.into())])),
+            ])
+        })
+        .collect();
+
+    let results: Vec<Json> = diagnostics
+        .iter()
+        .map(|diag| {
+            let level: &str = match diag.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Note | Severity::Help => "note",
+            };
+            let related: Vec<Json> = diag
+                .labels
+                .iter()
+                .map(|l| sarif_location(sources, l.span, Some(&l.message)))
+                .collect();
+
+            // sarif wants each fix as a set of replacements per file, ours only ever touch the one their span is in
+            let fixes: Vec<Json> = diag
+                .fixes
+                .iter()
+                .map(|fix| {
+                    let deleted: Json = Json::obj([
+                        ("byteOffset", fix.span.start.into()),
+                        ("byteLength", (fix.span.end - fix.span.start).into()),
+                    ]);
+                    Json::obj([
+                        ("description", Json::obj([("text", fix.message.as_str().into())])),
+                        (
+                            "artifactChanges",
+                            Json::Arr(vec![Json::obj([
+                                ("artifactLocation", Json::obj([("uri", sources.path(fix.span.file).into())])),
+                                (
+                                    "replacements",
+                                    Json::Arr(vec![Json::obj([
+                                        ("deletedRegion", deleted),
+                                        ("insertedContent", Json::obj([("text", fix.replacement.as_str().into())])),
+                                    ])]),
+                                ),
+                            ])]),
+                        ),
+                    ])
+                })
+                .collect();
+
+            Json::obj([
+                ("ruleId", diag.err.code().into()),
+                ("level", level.into()),
+                ("message", Json::obj([("text", diag.message().into())])),
+                ("locations", Json::Arr(vec![sarif_location(sources, diag.span, None)])),
+                ("relatedLocations", Json::Arr(related)),
+                ("fixes", Json::Arr(fixes)),
+            ])
+        })
+        .collect();
+
+    Json::obj([
+        ("$schema", "https://json.schemastore.org/sarif-2.1.0.json".into()),
+        ("version", "2.1.0".into()),
+        (
+            "runs",
+            Json::Arr(vec![Json::obj([
+                (
+                    "tool",
+                    Json::obj([(
+                        "driver",
+                        Json::obj([
+                            ("name", "stick".into()),
+                            ("version", env!("CARGO_PKG_VERSION").into()),
+                            ("rules", Json::Arr(rules)),
+                        ]),
+                    )]),
+                ),
+                ("results", Json::Arr(results)),
+            ])]),
+        ),
+    ])
+}
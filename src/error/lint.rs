@@ -0,0 +1,204 @@
+use super::{Diagnostic, Severity, SyntaxError, Warning};
+
+/// every category of warning, these are the names you pass to -W/-A/-D
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lint {
+    UnnecessarySemicolon,
+    UnusedVariable,
+
+    // nothing fires this until the parser makes return/break/continue (see `explain W0003`)
+    UnreachableCode,
+    Shadowing,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 4] = [
+        Lint::UnnecessarySemicolon,
+        Lint::UnusedVariable,
+        Lint::UnreachableCode,
+        Lint::Shadowing,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnnecessarySemicolon => "unnecessary-semicolon",
+            Lint::UnusedVariable => "unused-variable",
+            Lint::UnreachableCode => "unreachable-code",
+            Lint::Shadowing => "shadowing",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|lint| lint.name() == name)
+    }
+}
+
+impl Warning {
+    /// which lint category this warning falls under
+    pub fn lint(&self) -> Lint {
+        match self {
+            Warning::UnnecessarySemicolon => Lint::UnnecessarySemicolon,
+            Warning::UnusedVariable(_) => Lint::UnusedVariable,
+            Warning::UnreachableCode => Lint::UnreachableCode,
+            Warning::Shadowing(_) => Lint::Shadowing,
+        }
+    }
+}
+
+/// what to do when a lint fires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+/// the level of every lint, everything warns by default
+#[derive(Debug, Clone)]
+pub struct Lints {
+    levels: [LintLevel; Lint::ALL.len()],
+}
+
+impl Default for Lints {
+    fn default() -> Self {
+        Lints {
+            levels: [LintLevel::Warn; Lint::ALL.len()],
+        }
+    }
+}
+
+impl Lints {
+    #[inline]
+    pub fn level(&self, lint: Lint) -> LintLevel {
+        self.levels[lint as usize]
+    }
+
+    /// set a lint by name, `warnings` hits all of them. returns false on an unknown name
+    pub fn set(&mut self, name: &str, level: LintLevel) -> bool {
+        if name == "warnings" {
+            self.levels = [level; Lint::ALL.len()];
+            return true;
+        }
+
+        match Lint::from_name(name) {
+            Some(lint) => {
+                self.levels[lint as usize] = level;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// drop allowed warnings and turn denied ones into errors. run once on everything collected
+    pub fn apply(&self, diagnostics: &mut Vec<Diagnostic>) {
+        diagnostics.retain_mut(|diag| {
+            let SyntaxError::Warning(w) = &diag.err else {
+                return true;
+            };
+
+            match self.level(w.lint()) {
+                LintLevel::Allow => false,
+                LintLevel::Warn => true,
+                LintLevel::Deny => {
+                    diag.severity = Severity::Error;
+                    true
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::SemaError, intern::Symbol, source::Span};
+
+    // one of each warning plus an error, in source order
+    fn diagnostics() -> Vec<Diagnostic> {
+        let x: Symbol = Symbol::intern("x");
+        let errs: [(SyntaxError, Severity); 4] = [
+            (SyntaxError::Warning(Warning::UnnecessarySemicolon), Severity::Warning),
+            (SyntaxError::Warning(Warning::UnusedVariable(x)), Severity::Warning),
+            (SyntaxError::Sema(SemaError::UnresolvedName(x)), Severity::Error),
+            (SyntaxError::Warning(Warning::Shadowing(x)), Severity::Warning),
+        ];
+        errs.into_iter()
+            .enumerate()
+            .map(|(i, (err, severity))| Diagnostic::new(Span::new(Default::default(), i, i + 1), err, severity))
+            .collect()
+    }
+
+    // what's left after applying, as code and severity
+    fn applied(lints: &Lints) -> Vec<(&'static str, Severity)> {
+        let mut diagnostics: Vec<Diagnostic> = diagnostics();
+        lints.apply(&mut diagnostics);
+        diagnostics.iter().map(|d| (d.err.code(), d.severity)).collect()
+    }
+
+    #[test]
+    fn warn_by_default() {
+        let expected: [(&str, Severity); 4] = [
+            ("W0001", Severity::Warning),
+            ("W0002", Severity::Warning),
+            ("E0102", Severity::Error),
+            ("W0004", Severity::Warning),
+        ];
+        assert_eq!(applied(&Lints::default()), expected);
+    }
+
+    #[test]
+    fn allow_removes() {
+        let mut lints: Lints = Lints::default();
+        assert!(lints.set("unused-variable", LintLevel::Allow));
+        assert_eq!(
+            applied(&lints),
+            [("W0001", Severity::Warning), ("E0102", Severity::Error), ("W0004", Severity::Warning)]
+        );
+
+        // errors aren't lints, allowing everything leaves them alone
+        assert!(lints.set("warnings", LintLevel::Allow));
+        assert_eq!(applied(&lints), [("E0102", Severity::Error)]);
+    }
+
+    #[test]
+    fn deny_promotes() {
+        let mut lints: Lints = Lints::default();
+        assert!(lints.set("shadowing", LintLevel::Deny));
+        assert_eq!(
+            applied(&lints),
+            [
+                ("W0001", Severity::Warning),
+                ("W0002", Severity::Warning),
+                ("E0102", Severity::Error),
+                ("W0004", Severity::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn warn_downgrades() {
+        // -D warnings -W unnecessary-semicolon: the later flag wins for that one lint
+        let mut lints: Lints = Lints::default();
+        assert!(lints.set("warnings", LintLevel::Deny));
+        assert!(lints.set("unnecessary-semicolon", LintLevel::Warn));
+        assert_eq!(
+            applied(&lints),
+            [
+                ("W0001", Severity::Warning),
+                ("W0002", Severity::Error),
+                ("E0102", Severity::Error),
+                ("W0004", Severity::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_names() {
+        let mut lints: Lints = Lints::default();
+        assert!(!lints.set("unused-variables", LintLevel::Allow));
+        assert_eq!(applied(&lints).len(), 4);
+        for lint in Lint::ALL {
+            assert_eq!(Lint::from_name(lint.name()), Some(lint));
+        }
+    }
+}
@@ -1,5 +1,13 @@
 pub mod diagnostic;
 pub mod errors;
+pub mod explain;
+pub mod fix;
+pub mod format;
+pub mod lint;
+pub mod suggest;
 
-pub use diagnostic::{Diagnostic, dump};
-pub use errors::{ParseError, SyntaxError, lex_err};
+pub use diagnostic::{Diagnostic, Severity, dump};
+pub use errors::{LexError, ManifestError, ParseError, SemaError, SyntaxError, Warning, lex_err};
+pub use explain::explain;
+pub use format::ErrorFormat;
+pub use lint::Lints;
@@ -0,0 +1,48 @@
+// every keyword the lexer knows (kept in sync with Token by hand) and the builtin type names, for catching typos
+// like `retrun` and `i23`. they're pre-interned, so the lists live with the interner
+pub use crate::intern::{BUILTIN_TYPES, KEYWORDS};
+
+// optimal string alignment distance: levenshtein plus swapping two neighbours counts as one edit,
+// since that's the most common typo there is (i23, retrun, whiel)
+fn distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+
+    // full table, names are short so whatever
+    let mut d: Vec<Vec<usize>> = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost: usize = (a[i - 1] != b[j - 1]) as usize;
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// the closest candidate to name, if any is close enough to be a typo (about a third of the name can be wrong).
+/// case is ignored when measuring, so `Strng` still finds `str`. ties go to whichever candidate came first
+pub fn did_you_mean<'c>(name: &str, candidates: impl IntoIterator<Item = &'c str>) -> Option<&'c str> {
+    let lower: String = name.to_lowercase();
+    let max: usize = name.chars().count().div_ceil(3).max(1);
+
+    let mut best: Option<(usize, &str)> = None;
+    for candidate in candidates {
+        if candidate == name {
+            continue;
+        }
+        let dist: usize = distance(&lower, &candidate.to_lowercase());
+        if dist <= max && best.is_none_or(|(d, _)| dist < d) {
+            best = Some((dist, candidate));
+        }
+    }
+    best.map(|(_, candidate)| candidate)
+}
@@ -0,0 +1,150 @@
+//! `stick fmt`: prints the tree back out as source in the one true style, with the comments put back where they were.
+//! the code itself comes from the ast's Display impls, this just handles layout between statements and comments
+use crate::{cli::EXIT_COMPILE, parser::ast::*};
+use std::{fmt::Write, ops::Range};
+
+// more than one newline in the gap means there was a blank line there
+fn blank_between(src: &str, from: usize, to: usize) -> bool {
+    src.get(from..to).is_some_and(|gap| gap.matches('\n').count() > 1)
+}
+
+/// format a whole file. statements each get their own line (so every semicolon goes), operators get one space
+/// either side, blocks get indented, and runs of blank lines collapse to one. comments stay attached to whatever
+/// they were next to: ones on their own line stay on their own line, ones at the end of a line stay at the end
+pub fn format(src: &str, ast: &Ast, comments: &[Range<usize>]) -> String {
+    let stmts: &[Stmt] = &ast.stmts;
+    let mut out: String = String::with_capacity(src.len());
+    let mut comments = comments.iter().peekable();
+    let mut last: usize = 0;
+
+    for (i, stmt) in stmts.iter().enumerate() {
+        let next: usize = stmts.get(i + 1).map_or(src.len(), |s| s.span.start);
+
+        // comments before this statement. block comments right in front of it on the same line stay there
+        let mut inline: bool = false;
+        while let Some(comment) = comments.next_if(|c| c.start < stmt.span.start) {
+            if !out.is_empty() && !inline && blank_between(src, last, comment.start) {
+                out.push('\n');
+            }
+
+            let text: &str = src[comment.clone()].trim_end();
+            out.push_str(text);
+            inline = text.starts_with("/*") && !src[comment.end..stmt.span.start].contains('\n');
+            out.push(if inline { ' ' } else { '\n' });
+            last = comment.end;
+        }
+
+        if !out.is_empty() && !inline && blank_between(src, last, stmt.span.start) {
+            out.push('\n');
+        }
+        let _ = write!(out, "{}", ast.show(stmt));
+        last = stmt.span.end;
+
+        // anything inside the statement or after it on the same line (before the next one starts) trails it.
+        // a // comment eats the rest of the line, so anything after one of those has to go on the next
+        let mut line_comment: bool = false;
+        while let Some(comment) =
+            comments.next_if(|c| c.start < stmt.span.end || (c.start < next && !src[last..c.start].contains('\n')))
+        {
+            if line_comment {
+                out.push('\n');
+            } else {
+                out.push(' ');
+            }
+            let text: &str = src[comment.clone()].trim_end();
+            out.push_str(text);
+            line_comment = text.starts_with("//");
+            last = last.max(comment.end);
+        }
+        out.push('\n');
+    }
+
+    // whatever's left at the bottom of the file
+    for comment in comments {
+        if !out.is_empty() && blank_between(src, last, comment.start) {
+            out.push('\n');
+        }
+        out.push_str(src[comment.clone()].trim_end());
+        out.push('\n');
+        last = comment.end;
+    }
+
+    out
+}
+
+/// what `fmt --check` exits with: 0 if the file is already formatted, EXIT_COMPILE if formatting would change it
+pub fn check(src: &str, formatted: &str) -> i32 {
+    match formatted == src {
+        true => 0,
+        false => EXIT_COMPILE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cli::Flags, error::Severity, lexer::lex, lexer::lex_lossless, parser::Parser, source::SourceMap};
+
+    fn fmt(src: &str) -> String {
+        let mut sources: SourceMap = SourceMap::new();
+        let file = sources.add("test.stick", src);
+        let lexed = lex(sources.get(file), &Flags::QUIET).unwrap_or_else(|_| panic!("didn't lex"));
+        let (ast, errors) = Parser::new(sources.get(file), &lexed).parse(&Flags::QUIET);
+        let codes: Vec<&str> = errors.iter().filter(|d| d.severity == Severity::Error).map(|d| d.err.code()).collect();
+        assert!(codes.is_empty(), "{src:?} has errors: {codes:?}");
+        format(src, &ast, &lex_lossless(src).comments())
+    }
+
+    #[test]
+    fn comments_before_statements() {
+        assert_eq!(fmt("// one\nlet x = 1;\n"), "// one\nlet x = 1\n");
+        assert_eq!(fmt("// one\n// two\nlet x = 1\n"), "// one\n// two\nlet x = 1\n");
+        assert_eq!(fmt("/* block */ let x = 1\n"), "/* block */ let x = 1\n");
+        assert_eq!(fmt("/* block */\nlet x = 1\n"), "/* block */\nlet x = 1\n");
+    }
+
+    #[test]
+    fn comments_after_statements() {
+        assert_eq!(fmt("let x = 1;   // after\n"), "let x = 1 // after\n");
+        assert_eq!(fmt("let x = 1 /* after */\n"), "let x = 1 /* after */\n");
+        assert_eq!(fmt("let x = /* inside */ 1\n"), "let x = 1 /* inside */\n");
+
+        // nothing after the last statement but comments
+        assert_eq!(fmt("let x = 1\n// the end\n"), "let x = 1\n// the end\n");
+        assert_eq!(fmt("let x = 1\n\n\n// the end\n"), "let x = 1\n\n// the end\n");
+    }
+
+    #[test]
+    fn comments_between_statements() {
+        assert_eq!(fmt("let x = 1\n// x is done\nlet y = 2\n"), "let x = 1\n// x is done\nlet y = 2\n");
+        assert_eq!(fmt("let x = 1 // x\nlet y = 2 // y\n"), "let x = 1 // x\nlet y = 2 // y\n");
+
+        // blank lines around a comment stay (one of them), and stay on the same side
+        assert_eq!(fmt("let x = 1\n\n\n// y\nlet y = 2\n"), "let x = 1\n\n// y\nlet y = 2\n");
+        assert_eq!(fmt("let x = 1\n// y\n\n\nlet y = 2\n"), "let x = 1\n// y\n\nlet y = 2\n");
+    }
+
+    #[test]
+    fn idempotent() {
+        let srcs: [&str; 6] = [
+            "let x = 1;   // after x\nlet y = x+2 /* inside */\n\n\n// between\n/* block */ let z = y*(x+1); ;\n// end\n",
+            "// only a comment\n",
+            "let x=1;let y=2;\nx = y\n",
+            "/* a */ /* b */ let x = -(1 + 2) * 3 // c\n",
+            "import std::io\nfrom std::io import print\n\n\n\nprint(1, 2)\n",
+            "let v = a.get(0)[1..] // trailing\n/* block\n   over lines */\nv += 1\n",
+        ];
+        for src in srcs {
+            let once: String = fmt(src);
+            assert_eq!(fmt(&once), once, "{src:?} formats differently the second time");
+        }
+    }
+
+    #[test]
+    fn check_fails_when_it_would_change() {
+        let src: &str = "let x=1; // one\n";
+        let formatted: String = fmt(src);
+        assert_eq!(check(src, &formatted), EXIT_COMPILE);
+        assert_eq!(check(&formatted, &fmt(&formatted)), 0);
+    }
+}
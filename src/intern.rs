@@ -0,0 +1,182 @@
+//! names, interned. every identifier and keyword in the ast is a Symbol, a u32 that indexes one global table of
+//! strings. comparing or hashing one is comparing or hashing a u32, and the ast doesn't borrow the
+//! source any more, so it can outlive the buffer it was parsed from (the repl, caches)
+//!
+//! keywords and builtin type names are interned up front in a fixed order, so they have known symbols (`sym::I32`)
+//! that can be matched on directly
+use core::fmt;
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, RwLock},
+};
+
+/// an interned string. two symbols are equal exactly when their strings are
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+// sets up the pre-interned symbols: a constant for each one (numbered in the order they're listed) and a list of
+// each group's strings
+macro_rules! symbols {
+    ($($list:ident { $($name:ident: $text:literal),* $(,)? })*) => {
+        // only here to number the symbols
+        #[allow(dead_code, non_camel_case_types, clippy::upper_case_acronyms)]
+        #[repr(u32)]
+        enum Prefilled {
+            $($($name),*),*
+        }
+
+        /// the pre-interned symbols, by name (not everything matches on them yet)
+        #[allow(dead_code)]
+        pub mod sym {
+            use super::{Prefilled, Symbol};
+
+            $($(pub const $name: Symbol = Symbol(Prefilled::$name as u32);)*)*
+        }
+
+        $(pub const $list: &[&str] = &[$($text),*];)*
+
+        // everything above in order, so the nth string interned gets Symbol(n)
+        const PREFILLED: &[&[&str]] = &[$($list),*];
+    };
+}
+
+symbols! {
+    KEYWORDS {
+        LET: "let", IF: "if", ELSE: "else", FN: "fn", WHILE: "while", DO: "do", FOR: "for", IN: "in",
+        RETURN: "return", BREAK: "break", CONTINUE: "continue", MATCH: "match", IMPORT: "import", FROM: "from",
+        MUTABLE: "mutable", CONST: "const", STATIC: "static", PUBLIC: "public", CLASS: "class", STRUCT: "struct",
+        ENUM: "enum", NOT: "not", AND: "and", OR: "or", TRUE: "true", FALSE: "false",
+    }
+    BUILTIN_TYPES {
+        I8: "i8", U8: "u8", I16: "i16", U16: "u16", I32: "i32", U32: "u32", I64: "i64", U64: "u64", F32: "f32",
+        F64: "f64", BOOL: "bool", CHAR: "char", STR: "str",
+    }
+}
+
+// strings are leaked into the table and never freed. there's one copy of each distinct name the compiler ever sees,
+// which is nothing next to the sources themselves, and it means as_str can hand out a &'static str
+#[derive(Default)]
+struct Interner {
+    names: HashMap<&'static str, Symbol>,
+    strings: Vec<&'static str>,
+}
+
+impl Interner {
+    fn prefilled() -> Interner {
+        let mut interner: Interner = Interner::default();
+        for &text in PREFILLED.iter().flat_map(|list| list.iter()) {
+            interner.insert(text);
+        }
+        interner
+    }
+
+    fn insert(&mut self, text: &str) -> Symbol {
+        let text: &'static str = Box::leak(text.into());
+        let symbol: Symbol = Symbol(self.strings.len() as u32);
+        self.strings.push(text);
+        self.names.insert(text, symbol);
+        symbol
+    }
+}
+
+// modules parse on different threads (-j), so the table's shared. names are almost always already in it, so most of
+// the time only the read lock is taken
+static INTERNER: LazyLock<RwLock<Interner>> = LazyLock::new(|| RwLock::new(Interner::prefilled()));
+
+impl Symbol {
+    pub fn intern(text: &str) -> Symbol {
+        if let Some(&symbol) = INTERNER.read().unwrap().names.get(text) {
+            return symbol;
+        }
+
+        // someone else might have added it between dropping the read lock and getting this one
+        let mut interner = INTERNER.write().unwrap();
+        match interner.names.get(text) {
+            Some(&symbol) => symbol,
+            None => interner.insert(text),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        INTERNER.read().unwrap().strings[self.0 as usize]
+    }
+
+    /// one of the builtin type names (they're interned together, so it's just a range check)
+    #[inline]
+    pub fn is_builtin_type(self) -> bool {
+        (sym::I8.0..=sym::STR.0).contains(&self.0)
+    }
+}
+
+// print as the string, the number means nothing to anyone
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefilled_symbols_are_their_strings() {
+        let all: Vec<&str> = PREFILLED.iter().flat_map(|list| list.iter().copied()).collect();
+        for (i, text) in all.iter().enumerate() {
+            assert_eq!(Symbol(i as u32).as_str(), *text);
+            assert_eq!(Symbol::intern(text), Symbol(i as u32), "{text}");
+        }
+
+        // spot check the constants against what they're named after
+        assert_eq!(sym::LET.as_str(), "let");
+        assert_eq!(sym::FALSE.as_str(), "false");
+        assert_eq!(sym::I8.as_str(), "i8");
+        assert_eq!(sym::I32.as_str(), "i32");
+        assert_eq!(sym::STR.as_str(), "str");
+        assert_eq!(KEYWORDS.len() + BUILTIN_TYPES.len(), all.len());
+    }
+
+    #[test]
+    fn builtin_types() {
+        for name in BUILTIN_TYPES {
+            assert!(Symbol::intern(name).is_builtin_type(), "{name}");
+        }
+        for name in KEYWORDS.iter().chain(&["string", "I32", "x"]) {
+            assert!(!Symbol::intern(name).is_builtin_type(), "{name}");
+        }
+    }
+
+    #[test]
+    fn same_text_same_symbol() {
+        let a: Symbol = Symbol::intern("intern_test_name");
+        let b: Symbol = Symbol::intern(&String::from("intern_test_name"));
+        assert_eq!(a, b);
+        assert_ne!(a, Symbol::intern("intern_test_other"));
+        assert_eq!(a.as_str(), "intern_test_name");
+        assert_eq!(a.to_string(), "intern_test_name");
+        assert_eq!(format!("{a:?}"), "\"intern_test_name\"");
+    }
+
+    #[test]
+    fn interning_from_many_threads() {
+        let names: Vec<String> = (0..50).map(|i| format!("intern_thread_{i}")).collect();
+        let per_thread: Vec<Vec<Symbol>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| names.iter().map(|n| Symbol::intern(n)).collect::<Vec<Symbol>>()))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for symbols in &per_thread {
+            assert_eq!(symbols, &per_thread[0]);
+        }
+        for (name, symbol) in names.iter().zip(&per_thread[0]) {
+            assert_eq!(symbol.as_str(), name);
+        }
+    }
+}
@@ -0,0 +1,440 @@
+//! tiny json value, just enough for machine readable output and the lsp (not worth pulling serde in for)
+use std::fmt::{self, Display, Formatter, Write};
+
+use crate::intern::Symbol;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Arr(Vec<Json>),
+
+    // keys stay in insertion order so output is stable
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// build an object from key/value pairs, `Json::obj([("a", 1.into())])`
+    pub fn obj<'k>(fields: impl IntoIterator<Item = (&'k str, Json)>) -> Json {
+        Json::Obj(fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+    }
+
+    /// field of an object, None for anything else
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Obj(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// walk down through nested objects, `msg.at(&["params", "textDocument", "uri"])`
+    pub fn at(&self, path: &[&str]) -> Option<&Json> {
+        path.iter().try_fold(self, |json, key| json.get(key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Json::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_arr(&self) -> Option<&[Json]> {
+        match self {
+            Json::Arr(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// parse a whole document, trailing garbage is an error
+    pub fn parse(src: &str) -> Result<Json, String> {
+        let mut parser: JsonParser<'_> = JsonParser { src: src.as_bytes(), pos: 0 };
+        let value: Json = parser.value()?;
+        parser.skip_ws();
+        match parser.pos == src.len() {
+            true => Ok(value),
+            false => Err(format!("trailing characters at byte {}", parser.pos)),
+        }
+    }
+}
+
+// plain recursive descent over the bytes, strings get decoded back to utf-8 as they're read
+struct JsonParser<'s> {
+    src: &'s [u8],
+    pos: usize,
+}
+
+impl JsonParser<'_> {
+    fn skip_ws(&mut self) {
+        while matches!(self.src.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_ws();
+        match self.src.get(self.pos) {
+            Some(b) if *b == byte => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(format!("expected '{}' at byte {}", byte as char, self.pos)),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        match self.src[self.pos..].starts_with(word.as_bytes()) {
+            true => {
+                self.pos += word.len();
+                Ok(value)
+            }
+            false => Err(format!("unexpected character at byte {}", self.pos)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_ws();
+        match self.src.get(self.pos) {
+            Some(b'n') => self.keyword("null", Json::Null),
+            Some(b't') => self.keyword("true", Json::Bool(true)),
+            Some(b'f') => self.keyword("false", Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::Str),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items: Vec<Json> = Vec::new();
+                self.skip_ws();
+                if self.src.get(self.pos) == Some(&b']') {
+                    self.pos += 1;
+                    return Ok(Json::Arr(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    self.skip_ws();
+                    match self.src.get(self.pos) {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Json::Arr(items));
+                        }
+                        _ => return Err(format!("expected ',' or ']' at byte {}", self.pos)),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut fields: Vec<(String, Json)> = Vec::new();
+                self.skip_ws();
+                if self.src.get(self.pos) == Some(&b'}') {
+                    self.pos += 1;
+                    return Ok(Json::Obj(fields));
+                }
+                loop {
+                    self.skip_ws();
+                    let key: String = self.string()?;
+                    self.eat(b':')?;
+                    fields.push((key, self.value()?));
+                    self.skip_ws();
+                    match self.src.get(self.pos) {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Json::Obj(fields));
+                        }
+                        _ => return Err(format!("expected ',' or '}}' at byte {}", self.pos)),
+                    }
+                }
+            }
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(format!("unexpected character at byte {}", self.pos)),
+        }
+    }
+
+    // ints stay ints, anything with a fraction or exponent becomes a float
+    fn number(&mut self) -> Result<Json, String> {
+        let start: usize = self.pos;
+        while matches!(self.src.get(self.pos), Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        // only ascii got consumed so this can't fail
+        let text: &str = std::str::from_utf8(&self.src[start..self.pos]).unwrap_or_default();
+        match text.parse::<i64>() {
+            Ok(n) => Ok(Json::Int(n)),
+            Err(_) => text.parse::<f64>().map(Json::Float).map_err(|_| format!("bad number '{text}'")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.src.get(self.pos) != Some(&b'"') {
+            return Err(format!("expected string at byte {}", self.pos));
+        }
+        self.pos += 1;
+
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            match self.src.get(self.pos) {
+                None => return Err("unterminated string".to_string()),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return String::from_utf8(out).map_err(|_| "invalid utf-8 in string".to_string());
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escaped: char = match self.src.get(self.pos) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let high: u32 = self.hex4()?;
+
+                            // anything outside the bmp comes in as a surrogate pair. a high half without a low one
+                            // after it is left alone (it comes out as a replacement char), so is a lone low one
+                            let mut code: u32 = high;
+                            if (0xD800..0xDC00).contains(&high) && self.src[self.pos + 1..].starts_with(b"\\u") {
+                                let at: usize = self.pos;
+                                self.pos += 2;
+                                match self.hex4()? {
+                                    low @ 0xDC00..0xE000 => code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00),
+                                    _ => self.pos = at,
+                                }
+                            }
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(format!("bad escape at byte {}", self.pos)),
+                    };
+                    let mut buf: [u8; 4] = [0; 4];
+                    out.extend_from_slice(escaped.encode_utf8(&mut buf).as_bytes());
+                    self.pos += 1;
+                }
+                Some(b) => {
+                    out.push(*b);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    // the 4 hex digits after \u. leaves pos on the last one
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits: &[u8] = self.src.get(self.pos + 1..self.pos + 5).ok_or("truncated \\u escape")?;
+        let code: u32 = std::str::from_utf8(digits)
+            .ok()
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| format!("bad \\u escape at byte {}", self.pos))?;
+        self.pos += 4;
+        Ok(code)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Self {
+        Json::Int(n as i64)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::Str(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::Str(s)
+    }
+}
+
+// interned names serialize as the name
+impl From<Symbol> for Json {
+    fn from(s: Symbol) -> Self {
+        Json::Str(s.to_string())
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(items: Vec<T>) -> Self {
+        Json::Arr(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(opt: Option<T>) -> Self {
+        opt.map_or(Json::Null, Into::into)
+    }
+}
+
+// quotes and escapes a string the way the spec wants
+fn write_str(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+// compact, everything on one line
+impl Display for Json {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Int(n) => write!(f, "{n}"),
+
+            // json has no nan or infinity
+            Json::Float(n) if n.is_finite() => write!(f, "{n}"),
+            Json::Float(_) => f.write_str("null"),
+            Json::Str(s) => write_str(f, s),
+            Json::Arr(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_char(']')
+            }
+            Json::Obj(fields) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_str(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(src: &str) -> Json {
+        Json::parse(src).unwrap_or_else(|e| panic!("{src}: {e}"))
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(parse("null"), Json::Null);
+        assert_eq!(parse(" true "), Json::Bool(true));
+        assert_eq!(parse("false"), Json::Bool(false));
+        assert_eq!(parse("\"hi\""), Json::Str("hi".to_string()));
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse("0"), Json::Int(0));
+        assert_eq!(parse("-42"), Json::Int(-42));
+        assert_eq!(parse("9223372036854775807"), Json::Int(i64::MAX));
+        assert_eq!(parse("1.5"), Json::Float(1.5));
+        assert_eq!(parse("-2e3"), Json::Float(-2000.0));
+        assert_eq!(parse("1E-2"), Json::Float(0.01));
+
+        // too big for an i64 still parses, just not exactly
+        assert_eq!(parse("9223372036854775808"), Json::Float(9223372036854775808.0));
+        assert!(Json::parse("1.2.3").is_err());
+        assert!(Json::parse("-").is_err());
+    }
+
+    #[test]
+    fn string_escapes() {
+        assert_eq!(parse(r#""a\"b\\c\/d""#), Json::Str("a\"b\\c/d".to_string()));
+        assert_eq!(parse(r#""\b\f\n\r\t""#), Json::Str("\u{8}\u{c}\n\r\t".to_string()));
+        assert_eq!(parse(r#""\u00e9\u5B57""#), Json::Str("é字".to_string()));
+
+        // raw utf-8 goes straight through
+        assert_eq!(parse("\"é😀\""), Json::Str("é😀".to_string()));
+
+        assert!(Json::parse(r#""\x""#).is_err());
+        assert!(Json::parse(r#""\u12""#).is_err());
+        assert!(Json::parse(r#""\u12g4""#).is_err());
+        assert!(Json::parse("\"open").is_err());
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        assert_eq!(parse(r#""\ud83d\ude00""#), Json::Str("😀".to_string()));
+        assert_eq!(parse(r#""a\uD834\uDD1Eb""#), Json::Str("a𝄞b".to_string()));
+
+        // halves on their own can't be chars, and a high half doesn't swallow an escape that isn't its low half
+        assert_eq!(parse(r#""\ud83d""#), Json::Str("\u{fffd}".to_string()));
+        assert_eq!(parse(r#""\ude00x""#), Json::Str("\u{fffd}x".to_string()));
+        assert_eq!(parse(r#""\ud83d\u0041""#), Json::Str("\u{fffd}A".to_string()));
+    }
+
+    #[test]
+    fn nesting() {
+        let json: Json = parse(r#" { "a" : [1, {"b": [] }, {}], "c": {"d": {"e": null}} } "#);
+        assert_eq!(json.at(&["c", "d", "e"]), Some(&Json::Null));
+        let items: &[Json] = json.get("a").and_then(Json::as_arr).unwrap();
+        assert_eq!(items[0], Json::Int(1));
+        assert_eq!(items[1].get("b"), Some(&Json::Arr(Vec::new())));
+        assert_eq!(items[2], Json::Obj(Vec::new()));
+
+        let deep: String = "[".repeat(100) + &"]".repeat(100);
+        assert!(Json::parse(&deep).is_ok());
+    }
+
+    #[test]
+    fn keys_keep_their_order() {
+        let json: Json = parse(r#"{"z": 1, "a": 2}"#);
+        assert_eq!(json.to_string(), r#"{"z":1,"a":2}"#);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        for bad in ["", "nul", "nulll", "[1,]", "[1 2]", "{\"a\" 1}", "{\"a\": 1,}", "{a: 1}", "[1] x", "{} {}", "\"a\"b"] {
+            assert!(Json::parse(bad).is_err(), "{bad} parsed");
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let json: Json = Json::obj([
+            ("text", "quote \" slash \\ newline \n tab \t bell \u{7} é 😀".into()),
+            ("n", Json::Int(-3)),
+            ("f", Json::Float(0.25)),
+            ("list", vec![Json::Null, Json::Bool(true)].into()),
+        ]);
+        assert_eq!(parse(&json.to_string()), json);
+    }
+}
@@ -1,29 +1,31 @@
 use super::Token;
-use crate::error::{Diagnostic, dump};
+use crate::{
+    cli::Flags,
+    error::{Diagnostic, Severity},
+    source::SourceFile,
+};
 use logos::{Lexer, Logos};
 use std::{ops::Range, result::Result, time::Instant};
 
-// so i can lex both tokens and spans
+// so i can lex both tokens and spans. comments get skipped, docs is just where the doc comments were so the parser
+// can hang them on whatever they document (lex_lossless is the one that keeps everything)
 pub struct Lexed<'src> {
     pub tokens: Vec<Token<'src>>,
     pub spans: Vec<Range<usize>>,
+    pub docs: Vec<Range<usize>>,
 }
 
 // would like guidance as to if i'm doing this cleanly or if i'm nesting too much
 // i like the if let syntax frm ocaml carrying over. v heavy emphasis on pattern matching
-// src is a horrible name its just the source file
-pub fn lex<'path, 'src>(
-    path: &'path str,
-    src: &'src str,
-    debug: bool,
-    fastfail: bool,
-) -> Result<Lexed<'src>, Vec<Diagnostic<'path, 'src>>> {
-    let mut errors: Vec<Diagnostic<'path, 'src>> = Vec::new();
+// file is the source file (out of the SourceMap). flags are the same ones the parser gets
+pub fn lex<'src>(file: &'src SourceFile, flags: &Flags) -> Result<Lexed<'src>, Vec<Diagnostic>> {
+    let Flags { debug, fastfail, quiet, .. } = *flags;
+    let src: &'src str = &file.src;
+    let mut errors: Vec<Diagnostic> = Vec::new();
     let mut tokens: Vec<Token<'src>> = Vec::new();
     let mut spans: Vec<Range<usize>> = Vec::new();
     let start: Instant = Instant::now();
 
-    let linecount: usize = src.lines().count();
     let mut lex: Lexer<'_, Token> = Token::lexer(src);
     while let Some(res) = lex.next() {
         match res {
@@ -38,13 +40,7 @@ pub fn lex<'path, 'src>(
 
             // any errors have types in the SyntaxError enum, Unknown by default
             Err(err) => {
-                let span: Range<usize> = lex.span();
-                let diagnostic: Diagnostic<'_, '_> = Diagnostic {
-                    path,
-                    src,
-                    span,
-                    err,
-                };
+                let diagnostic: Diagnostic = Diagnostic::new(file.span(lex.span()), err, Severity::Error);
                 errors.push(diagnostic);
 
                 // fail immediately on ff
@@ -57,21 +53,23 @@ pub fn lex<'path, 'src>(
     }
 
     // handle debug prints
-    println!(
-        "Lexed {} bytes, {linecount} lines into {} tokens. Took {}s.",
-        src.len(),
-        tokens.len(),
-        start.elapsed().as_secs_f64()
-    );
-
-    if debug && !errors.is_empty() {
-        dump(&errors, "lastrun.log").unwrap_or_else(|_| eprintln!("Failed to dump errors."));
-        println!("Dumped all errors to a log file.");
+    if !quiet {
+        let took: f64 = start.elapsed().as_secs_f64();
+        println!(
+            "Lexed {} bytes, {} lines into {} tokens. Took {took}s.",
+            src.len(),
+            file.lines().line_count(),
+            tokens.len(),
+        );
     }
 
     // any errors stop at the lexing stage
     if errors.is_empty() {
-        Ok(Lexed { tokens, spans })
+        Ok(Lexed {
+            tokens,
+            spans,
+            docs: lex.extras,
+        })
     } else {
         Err(errors)
     }
@@ -1,5 +1,7 @@
 pub mod lex;
 pub mod token;
+pub mod trivia;
 
 pub use lex::lex;
 pub use token::Token;
+pub use trivia::{TokenStream, lex_lossless};
@@ -3,18 +3,22 @@ use std::fmt::Display;
 
 // the lexer itself, the big beef (logos specs look a lil ugly so don't count this in any PRs)
 use crate::error::{SyntaxError, lex_err};
-use logos::{Logos, skip};
+use logos::{Lexer, Logos, Skip};
+use std::ops::Range;
 
 // the entire token spec. this also doubles as the lexer itself when we run Token::lexer()
 #[derive(Logos, Default, Debug, PartialEq)]
-#[logos(error(SyntaxError<'s>, lex_err))] // TODO: fully understand why 's shuts this up, and why i can't use '_ or 'src
+#[logos(error(SyntaxError, lex_err))]
+#[logos(extras = Vec<Range<usize>>)] // where the doc comments were, `doc` fills it in
 #[logos(skip r"[ \t\f\r]+")] // ignore tabs, form feeds, and carriage returns
+// comments are skipped too. lex_lossless finds them again in the gaps between tokens for the tools that want them
+#[logos(skip r"//[^\n]*")]
+#[logos(skip r"/\*([^*]|\*+[^*/])*\*+/")]
+// doc comments (`///` and `/** */`) document whatever comes after them. they'd match the plain comment regexes too,
+// hence the priority. `////` and `/***/` stay plain comments, same as rustdoc. skipped as well, but where they are
+// goes in the lexer's extras so the parser can hang them on declarations
+#[logos(skip(r"///([^/\n][^\n]*)?|/\*\*[^*/]([^*]|\*+[^*/])*\*+/", doc, priority = 10))]
 pub enum Token<'src> {
-    // comments (skipped)
-    #[regex(r"//[^\r]*", skip, allow_greedy = true)]
-    #[regex(r"/\*([^*]|\*+[^*/])*\*+/", skip)]
-    Comment,
-
     // newlines (also skipped but either that or a semicolon is forced to seperate statements)
     #[regex(r"\n")] Newline,
 
@@ -196,4 +200,10 @@ impl<'src> Display for Token<'src> {
             _ => write!(f, "{:?}", self),
         }
     }
-}
\ No newline at end of file
+}
+// note down where a doc comment is and skip it
+fn doc<'src>(lex: &mut Lexer<'src, Token<'src>>) -> Skip {
+    let span: Range<usize> = lex.span();
+    lex.extras.push(span);
+    Skip
+}
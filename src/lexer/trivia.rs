@@ -0,0 +1,216 @@
+use super::Token;
+use logos::Logos;
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::Range,
+};
+
+/// everything the compiler throws away between tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    Comment,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia<'src> {
+    pub kind: TriviaKind,
+    pub text: &'src str,
+    pub span: Range<usize>,
+}
+
+/// a token plus the trivia around it. newlines end statements in stick so they're real tokens here, not trivia.
+/// trivia after a newline (indentation, whole line comments) leads the next token, anything else on a line trails
+/// the token before it. text is kept even for tokens that failed to lex, so nothing ever goes missing
+#[derive(Debug, PartialEq)]
+pub struct RichToken<'src> {
+    pub token: Token<'src>,
+    pub text: &'src str,
+    pub span: Range<usize>,
+    pub leading: Vec<Trivia<'src>>,
+    pub trailing: Vec<Trivia<'src>>,
+}
+
+/// the whole file as tokens and trivia, every byte accounted for. printing it gives back the exact source
+#[derive(Debug, PartialEq)]
+pub struct TokenStream<'src> {
+    pub tokens: Vec<RichToken<'src>>,
+
+    // trivia after the last token (or the whole file if it's nothing but comments)
+    pub eof: Vec<Trivia<'src>>,
+}
+
+// trivia either trails the token before it or waits to lead the next one
+fn attach<'src>(tokens: &mut [RichToken<'src>], pending: &mut Vec<Trivia<'src>>, trivia: Trivia<'src>) {
+    match tokens.last_mut() {
+        Some(prev) if prev.token != Token::Newline => prev.trailing.push(trivia),
+        _ => pending.push(trivia),
+    }
+}
+
+// logos skips whitespace and comments (doc ones too), so a gap between tokens is made of exactly those. splits one
+// back up into runs of whitespace and whole comments
+fn gap<'src>(src: &'src str, range: Range<usize>) -> impl Iterator<Item = Trivia<'src>> {
+    let mut at: usize = range.start;
+    std::iter::from_fn(move || {
+        let rest: &str = &src[at..range.end];
+        let (kind, len): (TriviaKind, usize) = match rest.as_bytes() {
+            [] => return None,
+            [b'/', b'/', ..] => (TriviaKind::Comment, rest.find('\n').unwrap_or(rest.len())),
+            [b'/', b'*', ..] => (TriviaKind::Comment, rest[2..].find("*/").map_or(rest.len(), |end| end + 4)),
+
+            // whitespace never has a / in it, so it runs up to the next comment
+            _ => (TriviaKind::Whitespace, rest.find('/').unwrap_or(rest.len())),
+        };
+        let span: Range<usize> = at..at + len;
+        at += len;
+        Some(Trivia {
+            kind,
+            text: &src[span.clone()],
+            span,
+        })
+    })
+}
+
+/// the slow, lossless way to lex (for tooling). the compile path keeps using lex(), which drops all of this
+pub fn lex_lossless(src: &str) -> TokenStream<'_> {
+    let mut tokens: Vec<RichToken<'_>> = Vec::new();
+    let mut pending: Vec<Trivia<'_>> = Vec::new();
+    let mut last: usize = 0;
+
+    for (res, span) in Token::lexer(src).spanned() {
+        for trivia in gap(src, last..span.start) {
+            attach(&mut tokens, &mut pending, trivia);
+        }
+        last = span.end;
+
+        tokens.push(RichToken {
+            token: res.unwrap_or(Token::Error),
+            text: &src[span.clone()],
+            span,
+            leading: std::mem::take(&mut pending),
+            trailing: Vec::new(),
+        });
+    }
+
+    // whatever's after the last token
+    for trivia in gap(src, last..src.len()) {
+        attach(&mut tokens, &mut pending, trivia);
+    }
+
+    TokenStream { tokens, eof: pending }
+}
+
+impl TokenStream<'_> {
+    /// where every comment is, in order
+    pub fn comments(&self) -> Vec<Range<usize>> {
+        let trivia = self.tokens.iter().flat_map(|tok| tok.leading.iter().chain(&tok.trailing)).chain(&self.eof);
+        trivia.filter(|t| t.kind == TriviaKind::Comment).map(|t| t.span.clone()).collect()
+    }
+}
+
+// byte for byte what came in
+impl Display for TokenStream<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for tok in &self.tokens {
+            for trivia in &tok.leading {
+                f.write_str(trivia.text)?;
+            }
+            f.write_str(tok.text)?;
+            for trivia in &tok.trailing {
+                f.write_str(trivia.text)?;
+            }
+        }
+        for trivia in &self.eof {
+            f.write_str(trivia.text)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCES: &[&str] = &[
+        "",
+        "let x = 1",
+        "let x = 1\n",
+        "// nothing but a comment",
+        "   \n\t\n",
+        "/// doc for x\nlet x: i32 = 1 // trailing\n\n/* block\n   over lines */ let y = x /* inline */ + 2\n",
+        "let a = 1\r\nlet b = 2\r\n// crlf comment\r\n",
+        "\tlet mutable t = 'c'\t; t = t\t// tabs\n",
+        "/** doc block */\nfn f()\n//// not a doc\n/***/\n",
+        "let s = \"// not a comment\" // but this is\n",
+        "let é = \"字😀\" /* 😀 */\n",
+        "let x = 5$ + @ // bad tokens stay too\n",
+        "let x = 1 // no newline at the end",
+        "/* unterminated block comment\nlet x = 1\n",
+    ];
+
+    #[test]
+    fn prints_back_exactly() {
+        for src in SOURCES {
+            assert_eq!(lex_lossless(src).to_string(), *src);
+        }
+    }
+
+    #[test]
+    fn every_byte_is_accounted_for_once() {
+        for src in SOURCES {
+            let stream: TokenStream<'_> = lex_lossless(src);
+            let mut at: usize = 0;
+            let mut covered = |span: &Range<usize>, text: &str| {
+                assert_eq!(span.start, at, "gap or overlap in {src:?}");
+                assert_eq!(&src[span.clone()], text);
+                at = span.end;
+            };
+            for tok in &stream.tokens {
+                tok.leading.iter().for_each(|t| covered(&t.span, t.text));
+                covered(&tok.span, tok.text);
+                tok.trailing.iter().for_each(|t| covered(&t.span, t.text));
+            }
+            stream.eof.iter().for_each(|t| covered(&t.span, t.text));
+            assert_eq!(at, src.len());
+        }
+    }
+
+    #[test]
+    fn comments_are_trivia() {
+        let src: &str = "/// doc\nlet x = 1 // tail\n\t/* a */ x\n// end";
+        let stream: TokenStream<'_> = lex_lossless(src);
+        let comments: Vec<&str> = stream.comments().into_iter().map(|c| &src[c]).collect();
+        assert_eq!(comments, ["/// doc", "// tail", "/* a */", "// end"]);
+
+        // a whole line comment leads the next token, one after code trails the token before it
+        assert_eq!(stream.tokens[0].leading[0].text, "/// doc");
+        let one: &RichToken<'_> = stream.tokens.iter().find(|t| t.text == "1").unwrap();
+        let trailing: Vec<&str> = one.trailing.iter().map(|t| t.text).collect();
+        assert_eq!(trailing, [" ", "// tail"]);
+        let x: &RichToken<'_> = stream.tokens.iter().rfind(|t| t.text == "x").unwrap();
+        let leading: Vec<(TriviaKind, &str)> = x.leading.iter().map(|t| (t.kind, t.text)).collect();
+        assert_eq!(
+            leading,
+            [(TriviaKind::Whitespace, "\t"), (TriviaKind::Comment, "/* a */"), (TriviaKind::Whitespace, " ")]
+        );
+        assert_eq!(stream.eof[0].text, "// end");
+    }
+
+    #[test]
+    fn compile_path_only_keeps_doc_ranges() {
+        use crate::{cli::Flags, lexer::lex, source::SourceMap};
+
+        let src: &str = "/// doc\nlet x = 1 // tail\n/** block doc */\n/* plain */ let y = 2\n";
+        let mut sources: SourceMap = SourceMap::new();
+        let id = sources.add("a.stick", src);
+        let bare = sources.add("b.stick", "\nlet x = 1 \n\n let y = 2\n");
+        let lexed = lex(sources.get(id), &Flags::QUIET).unwrap_or_else(|_| panic!("didn't lex"));
+        let docs: Vec<&str> = lexed.docs.iter().map(|d| &src[d.clone()]).collect();
+        assert_eq!(docs, ["/// doc", "/** block doc */"]);
+
+        // and the tokens are the same as if the comments were never there
+        let bare = lex(sources.get(bare), &Flags::QUIET).unwrap_or_else(|_| panic!("didn't lex"));
+        assert_eq!(lexed.tokens, bare.tokens);
+    }
+}
@@ -0,0 +1,16 @@
+//! the compiler as a library. the stick binary is a thin cli over this, and it's what the benches link against
+pub mod cli;
+pub mod doc;
+pub mod emit;
+pub mod error;
+pub mod formatter;
+pub mod intern;
+pub mod json;
+pub mod lexer;
+pub mod lsp;
+pub mod parallel;
+pub mod parser;
+pub mod project;
+pub mod sema;
+pub mod source;
+pub mod watch;
@@ -0,0 +1,4 @@
+pub mod server;
+pub mod transport;
+
+pub use server::run;
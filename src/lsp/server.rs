@@ -0,0 +1,586 @@
+use super::transport::{read_message, write_message};
+use crate::{
+    cli::Flags,
+    error::{
+        Diagnostic, Lints, Severity,
+        suggest::{BUILTIN_TYPES, KEYWORDS},
+    },
+    json::Json,
+    lexer::{
+        Token, TokenStream, lex, lex_lossless,
+        trivia::{Trivia, TriviaKind},
+    },
+    parser::{
+        Parser,
+        ast::{Ast, Span, Symbol},
+    },
+    sema::{SymbolIndex, index, resolve},
+    source::{Encoding, FileId, LineCol, LineIndex, SourceFile, SourceMap},
+};
+use std::{
+    collections::HashMap,
+    io,
+    ops::Range,
+    process::exit,
+};
+
+// semantic token types/modifiers we hand out, the indices in here are what goes over the wire
+const TOKEN_TYPES: &[&str] = &["keyword", "variable", "type", "string", "number", "operator", "comment"];
+const TOKEN_MODIFIERS: &[&str] = &["declaration", "readonly"];
+
+// lsp positions are 0 based (line, utf-16 column), everything in here is byte offsets
+fn position(lines: &LineIndex, offset: usize) -> (usize, usize) {
+    let pos: LineCol = lines.line_col(offset, Encoding::Utf16);
+    (pos.line - 1, pos.col - 1)
+}
+
+fn range(lines: &LineIndex, span: Span) -> Json {
+    let (start, end) = (position(lines, span.start), position(lines, span.end));
+    Json::obj([
+        ("start", Json::obj([("line", start.0.into()), ("character", start.1.into())])),
+        ("end", Json::obj([("line", end.0.into()), ("character", end.1.into())])),
+    ])
+}
+
+// everything the compiler knows about one open file
+struct Analysis {
+    diagnostics: Vec<Diagnostic>,
+    index: SymbolIndex,
+}
+
+// the usual lex -> parse -> resolve, quietly. lex errors mean no tree, so no symbols either
+fn analyze(file: &SourceFile) -> Analysis {
+    let lexed = match lex(file, &Flags::QUIET) {
+        Ok(lexed) => lexed,
+        Err(diagnostics) => {
+            return Analysis {
+                diagnostics,
+                index: SymbolIndex::default(),
+            };
+        }
+    };
+
+    let (ast, mut diagnostics): (Ast, Vec<Diagnostic>) = Parser::new(file, &lexed).parse(&Flags::QUIET);
+    diagnostics.extend(resolve(file, &ast));
+    Lints::default().apply(&mut diagnostics);
+
+    Analysis {
+        diagnostics,
+        index: index(file, &ast),
+    }
+}
+
+// file:///home/me/a%20b.stick -> /home/me/a b.stick
+fn uri_to_path(uri: &str) -> String {
+    let raw: &str = uri.strip_prefix("file://").unwrap_or(uri);
+    let mut out: Vec<u8> = Vec::new();
+    let bytes: &[u8] = raw.as_bytes();
+    let mut i: usize = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(std::str::from_utf8(h).ok()?, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn response(id: &Json, result: Json) -> Json {
+    Json::obj([("jsonrpc", "2.0".into()), ("id", id.clone()), ("result", result)])
+}
+
+fn error_response(id: &Json, code: i64, message: &str) -> Json {
+    Json::obj([
+        ("jsonrpc", "2.0".into()),
+        ("id", id.clone()),
+        ("error", Json::obj([("code", Json::Int(code)), ("message", message.into())])),
+    ])
+}
+
+fn notification(method: &str, params: Json) -> Json {
+    Json::obj([("jsonrpc", "2.0".into()), ("method", method.into()), ("params", params)])
+}
+
+/// open documents by uri, always the full text (we ask for full sync). closed ones stay in the SourceMap
+/// (FileIds have to stay good), but get emptied out
+struct Server {
+    sources: SourceMap,
+    docs: HashMap<String, FileId>,
+    shutdown: bool,
+}
+
+impl Server {
+    // requests get exactly one response back, notifications get whatever they cause (usually diagnostics)
+    fn handle(&mut self, msg: &Json) -> Vec<Json> {
+        let method: &str = msg.get("method").and_then(Json::as_str).unwrap_or_default();
+        let params: &Json = msg.get("params").unwrap_or(&Json::Null);
+
+        let Some(id) = msg.get("id") else {
+            return self.notify(method, params);
+        };
+
+        let result: Json = match method {
+            "initialize" => capabilities(),
+            "shutdown" => {
+                self.shutdown = true;
+                Json::Null
+            }
+            "textDocument/hover" => self.hover(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/references" => self.references(params),
+            "textDocument/documentSymbol" => self.symbols(params),
+            "textDocument/semanticTokens/full" => self.semantic_tokens(params),
+            "textDocument/completion" => self.completion(params),
+            _ => return vec![error_response(id, -32601, &format!("unsupported method: {method}"))],
+        };
+        vec![response(id, result)]
+    }
+
+    fn notify(&mut self, method: &str, params: &Json) -> Vec<Json> {
+        let uri: String = params.at(&["textDocument", "uri"]).and_then(Json::as_str).unwrap_or_default().to_string();
+
+        match method {
+            "textDocument/didOpen" => {
+                let text: &str = params.at(&["textDocument", "text"]).and_then(Json::as_str).unwrap_or_default();
+                self.open(&uri, text);
+                vec![self.publish(&uri)]
+            }
+
+            // full sync, so the last change is the whole new file
+            "textDocument/didChange" => {
+                let changes: &[Json] = params.get("contentChanges").and_then(Json::as_arr).unwrap_or_default();
+                if let Some(text) = changes.last().and_then(|c| c.get("text")).and_then(Json::as_str) {
+                    self.open(&uri, text);
+                }
+                vec![self.publish(&uri)]
+            }
+
+            // clear out whatever we published so it doesn't linger in the editor
+            "textDocument/didClose" => {
+                if let Some(file) = self.docs.remove(&uri) {
+                    self.sources.set(file, "");
+                }
+                vec![notification(
+                    "textDocument/publishDiagnostics",
+                    Json::obj([("uri", uri.into()), ("diagnostics", Json::Arr(Vec::new()))]),
+                )]
+            }
+
+            "exit" => exit(if self.shutdown { 0 } else { 1 }),
+
+            // initialized, $/cancelRequest, etc. nothing to do
+            _ => Vec::new(),
+        }
+    }
+
+    // a document's new text, reusing its FileId if it's been open before
+    fn open(&mut self, uri: &str, text: &str) {
+        match self.docs.get(uri) {
+            Some(&file) => self.sources.set(file, text),
+            None => {
+                let file: FileId = self.sources.add(uri_to_path(uri), text);
+                self.docs.insert(uri.to_string(), file);
+            }
+        }
+    }
+
+    // the document and a byte offset for requests that carry a position
+    fn locate<'p>(&'p self, params: &'p Json) -> Option<(&'p str, &'p SourceFile, usize)> {
+        let uri: &str = params.at(&["textDocument", "uri"])?.as_str()?;
+        let doc: &SourceFile = self.document(params)?;
+        let line: i64 = params.at(&["position", "line"])?.as_i64()?;
+        let col: i64 = params.at(&["position", "character"])?.as_i64()?;
+
+        // positions come from the client, so a negative or huge one is just no position (null), not a crash
+        let pos: LineCol = LineCol {
+            line: usize::try_from(line).ok()?.checked_add(1)?,
+            col: usize::try_from(col).ok()?.checked_add(1)?,
+        };
+        Some((uri, doc, doc.lines().offset(pos, Encoding::Utf16)))
+    }
+
+    // the document at a uri, for requests that are about a whole file
+    fn document(&self, params: &Json) -> Option<&SourceFile> {
+        let uri: &str = params.at(&["textDocument", "uri"])?.as_str()?;
+        Some(self.sources.get(*self.docs.get(uri)?))
+    }
+
+    fn publish(&self, uri: &str) -> Json {
+        let Some(&file) = self.docs.get(uri) else {
+            return notification(
+                "textDocument/publishDiagnostics",
+                Json::obj([("uri", uri.into()), ("diagnostics", Json::Arr(Vec::new()))]),
+            );
+        };
+        let doc: &SourceFile = self.sources.get(file);
+        let lines: &LineIndex = doc.lines();
+        let analysis: Analysis = analyze(doc);
+
+        let diagnostics: Vec<Json> = analysis
+            .diagnostics
+            .iter()
+            .map(|d| {
+                let severity: usize = match d.severity {
+                    Severity::Error => 1,
+                    Severity::Warning => 2,
+                    Severity::Note => 3,
+                    Severity::Help => 4,
+                };
+
+                // helps go under the message since editors don't have anywhere else to put them
+                let mut message: String = d.message();
+                for help in std::iter::once(d.err.help()).chain(d.helps.iter().map(String::as_str)) {
+                    message.push_str(&format!("\nhelp: {help}"));
+                }
+
+                let related: Vec<Json> = d
+                    .labels
+                    .iter()
+                    .map(|label| {
+                        Json::obj([
+                            (
+                                "location",
+                                Json::obj([("uri", uri.into()), ("range", range(lines, label.span))]),
+                            ),
+                            ("message", label.message.as_str().into()),
+                        ])
+                    })
+                    .collect();
+
+                Json::obj([
+                    ("range", range(lines, d.span)),
+                    ("severity", severity.into()),
+                    ("code", d.err.code().into()),
+                    ("source", "stick".into()),
+                    ("message", message.into()),
+                    ("relatedInformation", Json::Arr(related)),
+                ])
+            })
+            .collect();
+
+        notification(
+            "textDocument/publishDiagnostics",
+            Json::obj([("uri", uri.into()), ("diagnostics", Json::Arr(diagnostics))]),
+        )
+    }
+
+    // `let mutable x: i32`, straight out of the source (no type checker yet, so inferred types just show as _)
+    fn hover(&self, params: &Json) -> Json {
+        let Some((_, doc, offset)) = self.locate(params) else {
+            return Json::Null;
+        };
+        let src: &str = &doc.src;
+        let analysis: Analysis = analyze(doc);
+        let Some(declared) = analysis.index.at(offset).map(|i| &analysis.index.declarations[i]) else {
+            return Json::Null;
+        };
+
+        let typ: &str = declared.typ.map_or("_", |t| &src[t.range()]);
+        let signature: &str = &src[declared.decl.start.min(declared.span.start)..declared.span.end];
+        let kind: &str = match (declared.fixed, declared.mutable) {
+            (true, _) => "fixed (const or static), can never be reassigned",
+            (false, true) => "mutable",
+            (false, false) => "immutable",
+        };
+
+        Json::obj([
+            (
+                "contents",
+                Json::obj([
+                    ("kind", "markdown".into()),
+                    ("value", format!("```stick\n{signature}: {typ}\n```\n{kind}").into()),
+                ]),
+            ),
+            ("range", range(doc.lines(), declared.span)),
+        ])
+    }
+
+    fn definition(&self, params: &Json) -> Json {
+        let Some((uri, doc, offset)) = self.locate(params) else {
+            return Json::Null;
+        };
+        let analysis: Analysis = analyze(doc);
+        match analysis.index.at(offset) {
+            Some(i) => Json::obj([
+                ("uri", uri.into()),
+                ("range", range(doc.lines(), analysis.index.declarations[i].span)),
+            ]),
+            None => Json::Null,
+        }
+    }
+
+    fn references(&self, params: &Json) -> Json {
+        let Some((uri, doc, offset)) = self.locate(params) else {
+            return Json::Null;
+        };
+        let analysis: Analysis = analyze(doc);
+        let Some(i) = analysis.index.at(offset) else {
+            return Json::Arr(Vec::new());
+        };
+
+        let declaration: bool = params.at(&["context", "includeDeclaration"]).and_then(Json::as_bool).unwrap_or(true);
+        let mut spans: Vec<Span> = analysis.index.references(i);
+        if declaration {
+            spans.insert(0, analysis.index.declarations[i].span);
+        }
+
+        Json::Arr(
+            spans
+                .into_iter()
+                .map(|span| Json::obj([("uri", uri.into()), ("range", range(doc.lines(), span))]))
+                .collect(),
+        )
+    }
+
+    // flat list, blocks don't parse yet so everything's top level anyway
+    fn symbols(&self, params: &Json) -> Json {
+        let Some(doc) = self.document(params) else {
+            return Json::Null;
+        };
+        let (src, lines): (&str, &LineIndex) = (&doc.src, doc.lines());
+        let analysis: Analysis = analyze(doc);
+
+        Json::Arr(
+            analysis
+                .index
+                .declarations
+                .iter()
+                .map(|declared| {
+                    // 13 = Variable, 14 = Constant
+                    let kind: usize = if declared.fixed { 14 } else { 13 };
+                    Json::obj([
+                        ("name", declared.name.into()),
+                        ("detail", declared.typ.map(|t| &src[t.range()]).into()),
+                        ("kind", kind.into()),
+                        ("range", range(lines, declared.decl.to(declared.span))),
+                        ("selectionRange", range(lines, declared.span)),
+                    ])
+                })
+                .collect(),
+        )
+    }
+
+    // straight off the Token enum. lexes with logos directly so a bad token doesn't kill highlighting for the whole file
+    fn semantic_tokens(&self, params: &Json) -> Json {
+        let Some(doc) = self.document(params) else {
+            return Json::Null;
+        };
+        let (src, lines): (&str, &LineIndex) = (&doc.src, doc.lines());
+        let analysis: Analysis = analyze(doc);
+
+        // every token is relative to the one before it: line delta, column delta (if same line), length, type, modifiers
+        let mut data: Vec<Json> = Vec::new();
+        let (mut prev_line, mut prev_col): (usize, usize) = (0, 0);
+        let mut emit = |range: Range<usize>, kind: usize, modifiers: usize| {
+            let (line, col) = position(lines, range.start);
+            let length: usize = src[range].encode_utf16().count();
+            let delta_col: usize = if line == prev_line { col - prev_col } else { col };
+            data.extend([line - prev_line, delta_col, length, kind, modifiers].map(Json::from));
+            (prev_line, prev_col) = (line, col);
+        };
+
+        // block comments can span lines, and semantic tokens aren't allowed to. just let the editor colour those
+        let comments = |trivia: &[Trivia<'_>], emit: &mut dyn FnMut(Range<usize>, usize, usize)| {
+            for t in trivia {
+                if t.kind == TriviaKind::Comment && !t.text.contains('\n') {
+                    emit(t.span.clone(), 6, 0);
+                }
+            }
+        };
+
+        // the lossless stream keeps going past bad tokens, so one typo doesn't kill highlighting for the whole file
+        let stream: TokenStream<'_> = lex_lossless(src);
+        for rich in &stream.tokens {
+            comments(&rich.leading, &mut emit);
+
+            let range: Range<usize> = rich.span.clone();
+            let mut modifiers: usize = 0;
+            let kind: Option<usize> = match &rich.token {
+                Token::Identifier(name) if Symbol::intern(name).is_builtin_type() => Some(2),
+                Token::Identifier(_) => {
+                    if let Some(declared) = analysis.index.at(range.start).map(|i| &analysis.index.declarations[i]) {
+                        if declared.span.range() == range {
+                            modifiers |= 1;
+                        }
+                        if declared.fixed {
+                            modifiers |= 2;
+                        }
+                    }
+                    Some(1)
+                }
+                Token::LitString(_) | Token::LitChar(_) => Some(3),
+                Token::LitInteger(_) | Token::LitFloat(_) => Some(4),
+                t if is_keyword(t) => Some(0),
+                t if is_operator(t) => Some(5),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                emit(range, kind, modifiers);
+            }
+
+            comments(&rich.trailing, &mut emit);
+        }
+        comments(&stream.eof, &mut emit);
+
+        Json::obj([("data", Json::Arr(data))])
+    }
+
+    // names declared before the cursor, then keywords and builtin types
+    fn completion(&self, params: &Json) -> Json {
+        let Some((_, doc, offset)) = self.locate(params) else {
+            return Json::Null;
+        };
+        let src: &str = &doc.src;
+        let analysis: Analysis = analyze(doc);
+
+        // later declarations shadow earlier ones, so walk backwards and keep the first of each name
+        let mut items: Vec<Json> = Vec::new();
+        let mut seen: Vec<Symbol> = Vec::new();
+        for declared in analysis.index.declarations.iter().rev() {
+            if declared.span.end <= offset && !seen.contains(&declared.name) {
+                seen.push(declared.name);
+
+                // 6 = Variable, 21 = Constant
+                let kind: usize = if declared.fixed { 21 } else { 6 };
+                items.push(Json::obj([
+                    ("label", declared.name.into()),
+                    ("kind", kind.into()),
+                    ("detail", declared.typ.map(|t| &src[t.range()]).into()),
+                ]));
+            }
+        }
+
+        // 14 = Keyword, 22 = Struct (closest thing to a builtin type)
+        for (names, kind) in [(KEYWORDS, 14usize), (BUILTIN_TYPES, 22)] {
+            items.extend(names.iter().map(|name| Json::obj([("label", (*name).into()), ("kind", kind.into())])));
+        }
+        Json::Arr(items)
+    }
+}
+
+fn capabilities() -> Json {
+    Json::obj([
+        (
+            "capabilities",
+            Json::obj([
+                // 1 = full document sync
+                ("textDocumentSync", Json::Int(1)),
+                ("hoverProvider", true.into()),
+                ("definitionProvider", true.into()),
+                ("referencesProvider", true.into()),
+                ("documentSymbolProvider", true.into()),
+                ("completionProvider", Json::obj([])),
+                (
+                    "semanticTokensProvider",
+                    Json::obj([
+                        (
+                            "legend",
+                            Json::obj([
+                                ("tokenTypes", TOKEN_TYPES.to_vec().into()),
+                                ("tokenModifiers", TOKEN_MODIFIERS.to_vec().into()),
+                            ]),
+                        ),
+                        ("full", true.into()),
+                    ]),
+                ),
+            ]),
+        ),
+        (
+            "serverInfo",
+            Json::obj([("name", "stick".into()), ("version", env!("CARGO_PKG_VERSION").into())]),
+        ),
+    ])
+}
+
+fn is_keyword(tok: &Token<'_>) -> bool {
+    matches!(
+        tok,
+        Token::Let
+            | Token::If
+            | Token::Else
+            | Token::Fn
+            | Token::While
+            | Token::Do
+            | Token::For
+            | Token::In
+            | Token::Return
+            | Token::Break
+            | Token::Continue
+            | Token::Match
+            | Token::Import
+            | Token::From
+            | Token::Mutable
+            | Token::Const
+            | Token::Static
+            | Token::Public
+            | Token::Class
+            | Token::Struct
+            | Token::Enum
+            | Token::LogicalNot
+            | Token::LogicalAnd
+            | Token::LogicalOr
+            | Token::Bool(_)
+            | Token::Unit
+            | Token::Underscore
+    )
+}
+
+// brackets and separators are left to the editor's own highlighting
+fn is_operator(tok: &Token<'_>) -> bool {
+    !matches!(
+        tok,
+        Token::Newline
+            | Token::LParen
+            | Token::RParen
+            | Token::LBracket
+            | Token::RBracket
+            | Token::LBrace
+            | Token::RBrace
+            | Token::Comma
+            | Token::Semicolon
+            | Token::Colon
+            | Token::Dot
+            | Token::Error
+    )
+}
+
+/// `stick lsp`: speak the language server protocol over stdin/stdout until the client says exit
+pub fn run() -> ! {
+    let stdin: io::Stdin = io::stdin();
+    let mut input: io::StdinLock<'_> = stdin.lock();
+    let mut out: io::StdoutLock<'_> = io::stdout().lock();
+    let mut server: Server = Server {
+        sources: SourceMap::new(),
+        docs: HashMap::new(),
+        shutdown: false,
+    };
+
+    loop {
+        let replies: Vec<Json> = match read_message(&mut input) {
+            Ok(Some(body)) => match Json::parse(&body) {
+                Ok(msg) => server.handle(&msg),
+                Err(e) => vec![error_response(&Json::Null, -32700, &e)],
+            },
+
+            // client went away without saying exit
+            Ok(None) => exit(1),
+            Err(e) => {
+                eprintln!("lsp: {e}");
+                exit(1);
+            }
+        };
+
+        for reply in replies {
+            if write_message(&mut out, &reply).is_err() {
+                exit(1);
+            }
+        }
+    }
+}
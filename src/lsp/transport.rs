@@ -0,0 +1,41 @@
+use crate::json::Json;
+use std::io::{self, BufRead, Write};
+
+/// one message off the wire (headers, blank line, then exactly Content-Length bytes of json).
+/// Ok(None) means the client hung up
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length: Option<usize> = None;
+    let mut line: String = String::new();
+
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let header: &str = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        // Content-Type is the only other header and it's always utf-8 json anyway
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse().ok();
+        }
+    }
+
+    let length: usize = length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body: Vec<u8> = vec![0; length];
+    input.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "message isn't utf-8"))
+}
+
+pub fn write_message(out: &mut impl Write, message: &Json) -> io::Result<()> {
+    let body: String = message.to_string();
+    write!(out, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    out.flush()
+}
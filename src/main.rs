@@ -1,106 +1,702 @@
 //! docs here soon i'm lazy as fuck
-mod error;
-mod lexer;
-mod parser;
 
 // gotta work on this name but now im tired
-use crate::{
-    error::{Diagnostic, dump},
-    lexer::lex,
-    parser::{Parser, ast::Stmt},
+use stick::{
+    doc, emit, formatter, lsp, parallel, project, usage,
+    cli::{Command, EXIT_COMPILE, EXIT_INTERNAL, EXIT_IO, EXIT_USAGE, Flags, Options},
+    emit::Emit,
+    error::{
+        Diagnostic, ErrorFormat, Severity, dump, explain,
+        explain::normalize,
+        fix::{apply_fixes, safe_fixes},
+        format::sarif,
+    },
+    lexer::{lex, lex_lossless},
+    parser::{Parser, ast::Ast},
+    project::{LoadError, MANIFEST, Manifest, Module, Profile, Project, cache::Stats},
+    sema::{Exports, resolve, resolve_module},
+    source::{FileId, SourceFile, SourceMap},
+    watch::Watcher,
 };
 
 use std::{
-    env::{Args, args},
-    fs::read_to_string,
-    iter::Skip,
+    collections::HashMap,
+    fs::{create_dir_all, read_to_string, write},
+    io::{Write, stdin, stdout},
+    path::{Path, PathBuf},
     process::exit,
+    rc::Rc,
+    time::Instant,
 };
 
-fn log_errors(errors: &Vec<Diagnostic<'_, '_>>, flags: Vec<bool>) {
-    for d in errors {
-        eprintln!("{d}");
+// prints everything, returns how many actual errors there were (warnings don't stop the build)
+fn log_errors(sources: &SourceMap, errors: &[Diagnostic], opts: &Options) -> usize {
+    match opts.format {
+        ErrorFormat::Human => errors.iter().for_each(|d| eprintln!("{}", d.render(sources))),
+        ErrorFormat::Json => errors.iter().for_each(|d| eprintln!("{}", d.to_json(sources))),
+        ErrorFormat::Sarif => eprintln!("{}", sarif(sources, errors)),
     }
 
-    // debug flag
-    if flags[0] {
-        dump(errors, "lastrun.log").unwrap_or_else(|_| eprintln!("Failed to dump errors."));
+    if opts.flags.debug {
+        match dump(sources, errors, "lastrun.log") {
+            Ok(()) => println!("Dumped all errors to a log file."),
+            Err(_) => eprintln!("Failed to dump errors."),
+        }
     }
 
-    println!("\n(!) {} errors found.", errors.len());
+    // the summary is only for people, anything reading json or sarif counts for itself
+    let count = |severity: Severity| errors.iter().filter(|d| d.severity == severity).count();
+    let (errs, warnings) = (count(Severity::Error), count(Severity::Warning));
+    if opts.format == ErrorFormat::Human {
+        println!("\n(!) {errs} errors, {warnings} warnings found.");
+        if !errors.is_empty() {
+            println!("for more on any of these, try `stick explain <code>`.");
+        }
+    }
+    errs
 }
 
-fn main() {
-    // handle cl args
-    let mut args: Skip<Args> = args().skip(1);
-    let mut path: Option<String> = None;
-
-    // flags live in a vector (0 = debug, 1 = fast fail. maybe hashmap but thats dumb cemantics i wanna setup the parser)
-    let mut flags: Vec<bool> = vec![false; 2];
-    while let Some(a) = args.next() {
-        match a.as_str() {
-            "-d" | "--debug" => flags[0] = true,
-            "-ff" | "--fastfail" => flags[1] = true,
-            "--" => {
-                if let Some(p) = args.next() {
-                    path = Some(p);
-                }
-                break;
-            }
+// `stick explain E0012`
+fn explain_code(code: Option<&str>) -> ! {
+    let code: &str = code.unwrap_or_else(|| {
+        usage!("explain needs an error code, like E0012\n");
+    });
 
-            // unknown args
-            s if s.starts_with('-') => usage!("unknown flag: {}\n", s),
-            s => {
-                if path.is_none() {
-                    path = Some(s.to_string())
-                }
-            }
+    match explain(code) {
+        Some(text) => {
+            println!("{}\n\n{text}", normalize(code));
+            exit(0);
         }
+        None => usage!("no such error code: {code}\n"),
     }
+}
 
-    // try and open properly
-    let path: String = path.unwrap_or_else(|| {
-        usage!();
-    });
-    let src: String = read_to_string(&path).unwrap_or_else(|e| {
+// anything that can't be read or written stops everything
+fn read_file(path: &str) -> String {
+    read_to_string(path).unwrap_or_else(|e| {
         eprintln!("failed to read {path}: {e}");
-        exit(0);
+        exit(EXIT_IO);
+    })
+}
+
+fn write_file(path: &str, contents: &str) {
+    write(path, contents).unwrap_or_else(|e| {
+        eprintln!("failed to write {path}: {e}");
+        exit(EXIT_IO);
     });
+}
+
+// --step: stop between stages until enter gets pressed (works anywhere there's a stdin, ctrl+d skips the rest)
+fn pause(flags: &Flags, msg: &str) {
+    if flags.step {
+        print!("{msg} ");
+        let _ = stdout().flush();
+        let _ = stdin().read_line(&mut String::new());
+    }
+}
 
-    // TODO: parse the returned tokens into an AST
-    let lexed = match lex(&path, &src, flags[0], flags[1]) {
+// lex, parse and resolve one file, handing back everything found (lints already applied)
+fn check(file: &SourceFile, opts: &Options) -> Vec<Diagnostic> {
+    let lexed = match lex(file, &opts.flags) {
         Ok(lexed) => lexed,
-        Err(errors) => {
-            log_errors(&errors, flags);
-            exit(0);
+        Err(errors) => return errors,
+    };
+    pause(&opts.flags, "lexed, press enter to continue to parsing.");
+
+    // the tree is always complete (errors become placeholder nodes), so resolution still runs on broken files.
+    // unless it's -ff, then the first syntax error is where it ends (same as a lex error)
+    let (ast, mut errors): (Ast, Vec<Diagnostic>) = Parser::new(file, &lexed).parse(&opts.flags);
+    if !failed_fast(&errors, &opts.flags) {
+        errors.extend(resolve(file, &ast));
+    }
+    opts.lints.apply(&mut errors);
+    errors
+}
+
+// -ff and there's already an error, so nothing after this should run
+fn failed_fast(errors: &[Diagnostic], flags: &Flags) -> bool {
+    flags.fastfail && errors.iter().any(|d| d.severity == Severity::Error)
+}
+
+// build and run stop here until there's a backend, instead of pretending something got made. the check still
+// passed though, so it's a note and a clean exit (scripts shouldn't read a good build as bad arguments)
+fn no_backend(what: &str, opts: &Options) -> ! {
+    if let Command::Build | Command::Run = opts.command {
+        let name: &str = if opts.command == Command::Build { "build" } else { "run" };
+        eprintln!("{what} checks out, but there's no backend to {name} it with yet (--emit gets you the stages there are).");
+    }
+    exit(0);
+}
+
+// `stick check`, `build` and `run` on one file (or just `stick file.stick`)
+fn compile(sources: &SourceMap, file: FileId, opts: &Options) -> ! {
+    if let Some(stage) = opts.emit {
+        emit_file(sources, file, stage, opts);
+    }
+
+    // warnings get printed but only errors stop us
+    let errors: Vec<Diagnostic> = check(sources.get(file), opts);
+    if !errors.is_empty() && log_errors(sources, &errors, opts) > 0 {
+        exit(EXIT_COMPILE);
+    }
+    pause(&opts.flags, "checked, press enter to continue to the opt layer. (not done yet)");
+    no_backend(sources.path(file), opts);
+}
+
+// the Stick.toml a project command works on, the nearest one up from dir
+fn find_manifest(dir: &Path) -> PathBuf {
+    project::find(dir).unwrap_or_else(|| {
+        usage!("no {MANIFEST} in {} or any folder above it (pass a file, or `stick new` a project)\n", dir.display());
+    })
+}
+
+// lex and parse these modules all at once (each one only needs its own source), then resolve them against each
+// other in order. diagnostics come back per module, in the order asked for, whichever thread got where first
+fn check_modules(project: &Project, which: &[usize], opts: &Options) -> Vec<Vec<Diagnostic>> {
+    // -d prints every token as it's made though, which would come out interleaved, so that goes one at a time
+    let flags: Flags = Flags {
+        quiet: true,
+        ..opts.flags
+    };
+    let jobs: usize = if opts.flags.debug { 1 } else { opts.jobs };
+    let start: Instant = Instant::now();
+    let parsed: Vec<Parsed> = parallel::map(jobs, which.len(), |n| {
+        let module: &Module = &project.modules[which[n]];
+        parse_file(project.sources.get(module.file), &flags)
+    });
+    if !opts.flags.quiet {
+        println!(
+            "Lexed and parsed {} of {} modules on {} threads. Took {}s.",
+            which.len(),
+            project.modules.len(),
+            jobs.min(which.len()).max(1),
+            start.elapsed().as_secs_f64()
+        );
+    }
+    pause(&opts.flags, "parsed, press enter to continue to name resolution.");
+
+    let mut found: Vec<Vec<Diagnostic>> = Vec::new();
+    for (&i, (ast, mut errors)) in which.iter().zip(parsed) {
+        let module: &Module = &project.modules[i];
+        let exports: Exports<'_> = project.exports(i);
+        if !failed_fast(&errors, &opts.flags) {
+            errors.extend(resolve_module(project.sources.get(module.file), &ast, &exports));
         }
+        opts.lints.apply(&mut errors);
+        found.push(errors);
+    }
+    found
+}
+
+// `stick check`, `build` and `run` with no file (or a folder): the whole project the nearest Stick.toml
+// describes. every module gets checked and everything found is reported together
+fn compile_project(dir: &Path, opts: &Options) -> ! {
+    if opts.emit.is_some() {
+        usage!("--emit works on one file at a time, pass it the file\n");
+    }
+    let manifest: PathBuf = find_manifest(dir);
+
+    let mut project: Project = match Project::load(&manifest, &mut |sources, errors| {
+        log_errors(sources, errors, opts);
+    }) {
+        Ok(project) => project,
+        Err(LoadError::Io(message)) => {
+            eprintln!("{message}");
+            exit(EXIT_IO);
+        }
+        Err(LoadError::Manifest) => exit(EXIT_COMPILE),
     };
 
-    if flags[0] {
-        press_btn_continue::wait("Press any button to continue to parsing.").unwrap();
-    }
-    let mut parser = Parser {
-        path: &path,
-        src: &src,
-        tokens: &lexed.tokens,
-        spans: &lexed.spans,
-        pos: 0,
-        fastfail: flags[1],
-        errors: Vec::new(),
+    // modules that checked clean last time with the same source, imports and lints don't need checking again.
+    // only clean ones get remembered, so anything with warnings always gets them printed
+    let lints: String = format!("{:?}", opts.lints);
+    let mut stale: Vec<usize> = Vec::new();
+    for i in 0..project.modules.len() {
+        let fingerprint: u64 = project.fingerprint(i, &lints);
+        if !project.cache.fresh(&project.modules[i].key, fingerprint) {
+            stale.push(i);
+        }
+    }
+
+    let mut errors: Vec<Diagnostic> = Vec::new();
+    let mut clean: Vec<usize> = Vec::new();
+    for (&i, found) in stale.iter().zip(check_modules(&project, &stale, opts)) {
+        if found.is_empty() {
+            clean.push(i);
+        }
+        errors.extend(found);
+    }
+
+    let errs: usize = if errors.is_empty() { 0 } else { log_errors(&project.sources, &errors, opts) };
+    drop(errors);
+    for i in clean {
+        let fingerprint: u64 = project.fingerprint(i, &lints);
+        project.checked(i, fingerprint);
+    }
+
+    // a cache that can't be written just means the next build starts from scratch
+    if let Err(e) = project.save_cache() {
+        eprintln!("couldn't write the build cache: {e}");
+    }
+    if opts.flags.debug {
+        let stats: Stats = project.cache.stats;
+        println!(
+            "cache: {} scanned, {} from cache. {} checked, {} from cache.",
+            stats.scan_misses, stats.scan_hits, stats.check_misses, stats.check_hits
+        );
+    }
+    if errs > 0 {
+        exit(EXIT_COMPILE);
+    }
+
+    // debug and release always exist, the manifest can only change what's in them
+    let root: &Manifest = &project.packages[0];
+    let profile: &Profile = root.profile(if opts.release { "release" } else { "debug" }).unwrap_or(&root.profiles[0]);
+    let optimized: &str = if profile.opt_level > 0 { "optimized" } else { "unoptimized" };
+    let debuginfo: &str = if profile.debug { " + debuginfo" } else { "" };
+    println!(
+        "checked {} v{} ({} modules, {} profile [{optimized}{debuginfo}])",
+        root.name,
+        root.version,
+        project.modules.len(),
+        profile.name
+    );
+    no_backend(&root.name, opts);
+}
+
+// what --watch keeps of a module between checks: the fingerprint it was checked with, what that found, and the
+// SourceMap it was found in (every reload makes a new one, and FileIds only mean anything in the map they came from)
+struct Watched {
+    fingerprint: u64,
+    sources: Rc<SourceMap>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+// wipe the terminal and put the cursor back up top
+fn clear_screen() {
+    print!("\x1b[2J\x1b[H");
+    let _ = stdout().flush();
+}
+
+// everything one round of --watch found, then what's going on
+fn print_watched<'w>(modules: impl IntoIterator<Item = &'w Watched>, status: &str) {
+    let (mut errs, mut warnings): (usize, usize) = (0, 0);
+    for module in modules {
+        for diag in &module.diagnostics {
+            eprintln!("{}", diag.render(&module.sources));
+            match diag.severity {
+                Severity::Error => errs += 1,
+                Severity::Warning => warnings += 1,
+                _ => {}
+            }
+        }
+    }
+    println!("\n(!) {errs} errors, {warnings} warnings found.");
+    println!("{status} watching for changes, ctrl+c to stop.");
+}
+
+// `stick check file.stick --watch`
+fn watch_file(path: &str, opts: &Options) -> ! {
+    let mut watcher: Watcher = Watcher::new();
+    watcher.set(&[PathBuf::from(path)]);
+    loop {
+        clear_screen();
+
+        // editors that save by deleting and rewriting leave a moment where there's nothing to read, so wait it out
+        let src: String = match read_to_string(path) {
+            Ok(src) => src,
+            Err(e) => {
+                eprintln!("failed to read {path}: {e}");
+                watcher.wait();
+                continue;
+            }
+        };
+        let mut sources: SourceMap = SourceMap::new();
+        let file: FileId = sources.add(path, src);
+        let module: Watched = Watched {
+            fingerprint: 0,
+            diagnostics: check(sources.get(file), opts),
+            sources: Rc::new(sources),
+        };
+        print_watched([&module], &format!("checked {path}."));
+        watcher.wait();
+    }
+}
+
+// `stick check --watch` on a project: reload it every time something changes, but only check the modules whose
+// source or imports actually changed. the rest print what they found last time
+fn watch_project(dir: &Path, opts: &Options) -> ! {
+    let manifest: PathBuf = find_manifest(dir);
+    let lints: String = format!("{:?}", opts.lints);
+    let mut watcher: Watcher = Watcher::new();
+    let mut last: HashMap<String, Watched> = HashMap::new();
+    let mut files: Vec<PathBuf> = vec![manifest.clone()];
+    loop {
+        clear_screen();
+        let mut project: Project = match Project::load(&manifest, &mut |sources, errors| {
+            log_errors(sources, errors, opts);
+        }) {
+            Ok(project) => project,
+
+            // keep watching what was there before, plus the manifest that needs fixing
+            Err(LoadError::Io(message)) => {
+                eprintln!("{message}");
+                watcher.set(&files);
+                watcher.wait();
+                continue;
+            }
+            Err(LoadError::Manifest) => {
+                println!("fix the manifest first, watching for changes, ctrl+c to stop.");
+                watcher.set(&files);
+                watcher.wait();
+                continue;
+            }
+        };
+
+        let fingerprints: Vec<u64> = (0..project.modules.len()).map(|i| project.fingerprint(i, &lints)).collect();
+        let stale: Vec<usize> = (0..project.modules.len())
+            .filter(|&i| last.get(&project.modules[i].key).is_none_or(|w| w.fingerprint != fingerprints[i]))
+            .collect();
+        let found: Vec<Vec<Diagnostic>> = check_modules(&project, &stale, opts);
+
+        // every module and every manifest, new .stick files next to any of them count too
+        files = project.modules.iter().map(|m| PathBuf::from(project.sources.path(m.file))).collect();
+        files.extend(project.packages.iter().map(|p| p.dir.join(MANIFEST)));
+
+        // the modules that didn't change hang on to the map from whenever they were last checked
+        let sources: Rc<SourceMap> = Rc::new(std::mem::take(&mut project.sources));
+        for (&i, diagnostics) in stale.iter().zip(found) {
+            let module: Watched = Watched {
+                fingerprint: fingerprints[i],
+                sources: Rc::clone(&sources),
+                diagnostics,
+            };
+            last.insert(project.modules[i].key.clone(), module);
+        }
+        last.retain(|key, _| project.modules.iter().any(|m| &m.key == key));
+
+        let status: String = format!("checked {} ({} of {} modules changed).", project.packages[0].name, stale.len(), project.modules.len());
+        print_watched(project.modules.iter().filter_map(|m| last.get(&m.key)), &status);
+        watcher.set(&files);
+        watcher.wait();
+    }
+}
+
+// `stick new demo`
+fn new_project(name: Option<&str>) -> ! {
+    let name: &str = name.unwrap_or_else(|| {
+        usage!("new needs a name for the project\n");
+    });
+    if !project::valid_name(name) {
+        usage!("'{name}' can't be a package name, use letters, digits, - and _ (starting with a letter)\n");
+    }
+
+    match project::scaffold(name) {
+        Ok(dir) => {
+            println!("created {name} in {}/, `cd {name}` and `stick check` to get going.", dir.display());
+            exit(0);
+        }
+        Err(e) => {
+            eprintln!("failed to create {name}: {e}");
+            exit(EXIT_IO);
+        }
+    }
+}
+
+// `stick fix file.stick`: keep applying safe fixes until there's none left (fixing one error can uncover the next),
+// then write the file back and report whatever still needs a human
+fn fix_file(mut sources: SourceMap, file: FileId, opts: &Options) -> ! {
+    let mut applied: usize = 0;
+
+    // capped in case two fixes ever undo each other
+    for _ in 0..16 {
+        let (fixed, n): (String, usize) = apply_fixes(sources.get(file), &safe_fixes(&check(sources.get(file), opts)));
+        if n == 0 {
+            break;
+        }
+        applied += n;
+        sources.set(file, fixed);
+    }
+
+    let path: &str = sources.path(file);
+    if applied > 0 {
+        write_file(path, sources.src(file));
+    }
+    println!("applied {applied} fixes to {path}.");
+
+    let errors: Vec<Diagnostic> = check(sources.get(file), opts);
+    if !errors.is_empty() && log_errors(&sources, &errors, opts) > 0 {
+        exit(EXIT_COMPILE);
+    }
+    exit(0);
+}
+
+// a tree and what went wrong making it
+type Parsed = (Ast, Vec<Diagnostic>);
+
+// lex and parse one file. one that doesn't lex gets no tree, just its errors
+fn parse_file(file: &SourceFile, flags: &Flags) -> Parsed {
+    match lex(file, flags) {
+        Ok(lexed) => Parser::new(file, &lexed).parse(flags),
+        Err(errors) => (Ast::new(), errors),
+    }
+}
+
+// parse without printing anything but errors, for tools that only want the tree back
+fn parse_quiet(file: &SourceFile) -> Result<Ast, Vec<Diagnostic>> {
+    let lexed = lex(file, &Flags::QUIET)?;
+
+    // warnings don't matter here, the only question is whether the tree is whole
+    let (ast, mut errors): (Ast, Vec<Diagnostic>) = Parser::new(file, &lexed).parse(&Flags::QUIET);
+    errors.retain(|d| d.severity == Severity::Error);
+    match errors.is_empty() {
+        true => Ok(ast),
+        false => Err(errors),
+    }
+}
+
+// `stick fmt file.stick` rewrites it in place, `--check` just says whether it would (and fails if so, for CI)
+fn fmt_file(mut sources: SourceMap, file: FileId, opts: &Options) -> ! {
+    let (path, src): (&str, &str) = (sources.path(file), sources.src(file));
+    let formatted: String = match parse_quiet(sources.get(file)) {
+        Ok(ast) => formatter::format(src, &ast, &lex_lossless(src).comments()),
+        Err(errors) => {
+            log_errors(&sources, &errors, opts);
+            println!("can't format {path} until it parses.");
+            exit(EXIT_COMPILE);
+        }
     };
 
-    let _ast: Vec<Stmt<'_>> = match parser.parse(&flags) {
-        Ok(ok) => ok,
+    // never write out something that doesn't parse, that'd be a formatter bug eating someone's code
+    let path: String = path.to_string();
+    let after: FileId = sources.add(path.as_str(), formatted);
+    if parse_quiet(sources.get(after)).is_err() {
+        eprintln!("formatting {path} produced invalid code, leaving it alone (this is a bug, please report it)");
+        exit(EXIT_INTERNAL);
+    }
+
+    let (src, formatted): (&str, &str) = (sources.src(file), sources.src(after));
+    let code: i32 = formatter::check(src, formatted);
+    if code == 0 {
+        println!("{path} is already formatted.");
+        exit(0);
+    }
+    if opts.check_only {
+        println!("would reformat {path}");
+        exit(code);
+    }
+
+    write_file(&path, formatted);
+    println!("formatted {path}.");
+    exit(0);
+}
+
+// a docs page, making whatever folders it needs on the way
+fn write_page(path: &Path, page: &str) {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        create_dir_all(dir).unwrap_or_else(|e| {
+            eprintln!("failed to create {}: {e}", dir.display());
+            exit(EXIT_IO);
+        });
+    }
+    write_file(&path.to_string_lossy(), page);
+}
+
+// `stick doc file.stick` writes doc/<module>.md (or into -o). on its own there's nothing else to link to, so
+// types from other modules are just named
+fn doc_file(sources: &SourceMap, file: FileId, opts: &Options) -> ! {
+    let path: &str = sources.path(file);
+    let ast: Ast = match parse_quiet(sources.get(file)) {
+        Ok(ast) => ast,
         Err(errors) => {
-            log_errors(&errors, flags);
-            exit(0);
+            log_errors(sources, &errors, opts);
+            println!("can't document {path} until it parses.");
+            exit(EXIT_COMPILE);
+        }
+    };
+
+    let module: &str = Path::new(path).file_stem().and_then(|s| s.to_str()).unwrap_or("main");
+    let (page, items): (String, usize) = doc::document(module, &ast, &[module]);
+    let out: PathBuf = Path::new(opts.out.as_deref().unwrap_or("doc")).join(doc::page(module));
+    write_page(&out, &page);
+    println!("documented {items} items in {}.", out.display());
+    exit(0);
+}
+
+// `stick doc` in a project: a page for every module in the package at doc/<module path>.md (next to Stick.toml,
+// or in -o), so `a::b::C` links land on a/b.md. dependencies aren't included, they get documented in their own
+// folder. a module that doesn't parse gets skipped, the rest still get written
+fn doc_project(dir: &Path, opts: &Options) -> ! {
+    let manifest: PathBuf = find_manifest(dir);
+    let project: Project = match Project::load(&manifest, &mut |sources, errors| {
+        log_errors(sources, errors, opts);
+    }) {
+        Ok(project) => project,
+        Err(LoadError::Io(message)) => {
+            eprintln!("{message}");
+            exit(EXIT_IO);
         }
+        Err(LoadError::Manifest) => exit(EXIT_COMPILE),
     };
 
-    if flags[0] {
-        press_btn_continue::wait(
-            "Press any button to continue to semantic analysis and the opt layer. (not done yet)",
-        )
-        .unwrap();
+    let modules: Vec<&Module> = project.modules.iter().filter(|m| m.package == 0).collect();
+    let pages: Vec<&str> = modules.iter().map(|m| m.name.as_str()).collect();
+    let out: PathBuf = opts.out.as_ref().map_or_else(|| project.packages[0].dir.join("doc"), PathBuf::from);
+
+    let (mut written, mut items, mut broken): (usize, usize, usize) = (0, 0, 0);
+    for module in modules {
+        let ast: Ast = match parse_quiet(project.sources.get(module.file)) {
+            Ok(ast) => ast,
+            Err(errors) => {
+                log_errors(&project.sources, &errors, opts);
+                broken += 1;
+                continue;
+            }
+        };
+        let (page, count): (String, usize) = doc::document(&module.name, &ast, &pages);
+        write_page(&out.join(doc::page(&module.name)), &page);
+        written += 1;
+        items += count;
+    }
+
+    println!("documented {items} items in {written} modules in {}.", out.display());
+    if broken > 0 {
+        println!("{broken} modules can't be documented until they parse.");
+        exit(EXIT_COMPILE);
+    }
+    exit(0);
+}
+
+// `--emit=ast` and friends: run just far enough to make that stage, write it out (to stdout unless -o says
+// otherwise) and stop. only errors get reported, stdout's meant to be nothing but the output here
+fn emit_file(sources: &SourceMap, file: FileId, stage: Emit, opts: &Options) -> ! {
+    if !stage.implemented() {
+        eprintln!("--emit={} isn't available yet, the compiler stops after name resolution for now.", stage.name());
+        exit(EXIT_USAGE);
+    }
+
+    let output: String = match stage {
+        Emit::Tokens => match lex(sources.get(file), &Flags::QUIET) {
+            Ok(lexed) => emit::tokens(&lexed),
+            Err(errors) => {
+                log_errors(sources, &errors, opts);
+                exit(EXIT_COMPILE);
+            }
+        },
+        _ => match parse_quiet(sources.get(file)) {
+            Ok(ast) if stage == Emit::Ast => emit::ast(&ast),
+            Ok(ast) => emit::ast_json(&ast),
+            Err(errors) => {
+                log_errors(sources, &errors, opts);
+                exit(EXIT_COMPILE);
+            }
+        },
+    };
+
+    match &opts.out {
+        Some(out) => write_file(out, &output),
+        None => print!("{output}"),
+    }
+    exit(0);
+}
+
+// check one line of the repl against everything before it. errors get printed and the line thrown out,
+// otherwise it gets printed back the way it parsed
+fn repl_line(sources: &SourceMap, session: FileId, start: usize, opts: &Options) -> bool {
+    let file: &SourceFile = sources.get(session);
+    let mut errors: Vec<Diagnostic> = Vec::new();
+    let mut tree: Ast = Ast::new();
+    match lex(file, &Flags::QUIET) {
+        Ok(lexed) => {
+            let (ast, parse_errors) = Parser::new(file, &lexed).parse(&Flags::QUIET);
+            errors.extend(parse_errors);
+            errors.extend(resolve(file, &ast));
+            opts.lints.apply(&mut errors);
+            tree = ast;
+        }
+        Err(lex_errors) => errors = lex_errors,
+    }
+
+    // unused variable warnings would fire on every single let, so only errors count in here
+    errors.retain(|d| d.severity == Severity::Error);
+    if !errors.is_empty() {
+        log_errors(sources, &errors, opts);
+        return false;
+    }
+    for stmt in tree.stmts.iter().filter(|s| s.span.start >= start) {
+        println!("{}", tree.show(stmt));
+    }
+    true
+}
+
+// `stick repl`: there's nothing to evaluate with yet, so this is a place to see how things parse and resolve,
+// one line at a time with everything from earlier lines still in scope
+fn repl(opts: &Options) -> ! {
+    println!("stick repl (no evaluator yet, so lines print back the way they parsed). :q or ctrl+d to quit.");
+    let mut sources: SourceMap = SourceMap::new();
+    let file: FileId = sources.add("<repl>", "");
+    let mut session: String = String::new();
+    let mut line: String = String::new();
+    loop {
+        print!("> ");
+        let _ = stdout().flush();
+        line.clear();
+        match stdin().read_line(&mut line) {
+            Ok(0) => exit(0),
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("failed to read stdin: {e}");
+                exit(EXIT_IO);
+            }
+        }
+
+        match line.trim() {
+            ":q" | ":quit" => exit(0),
+            "" => continue,
+            _ => {}
+        }
+        let start: usize = session.len();
+        session.push_str(line.trim_end());
+        session.push('\n');
+        sources.set(file, session.as_str());
+        if !repl_line(&sources, file, start, opts) {
+            session.truncate(start);
+        }
+    }
+}
+
+fn main() {
+    let opts: Options = Options::parse();
+
+    match &opts.command {
+        Command::Explain(code) => explain_code(code.as_deref()),
+        Command::Lsp => lsp::run(),
+        Command::Repl => repl(&opts),
+        Command::New(name) => new_project(name.as_deref()),
+        Command::Doc => match opts.path.as_deref() {
+            None => doc_project(Path::new(""), &opts),
+            Some(dir) if Path::new(dir).is_dir() => doc_project(Path::new(dir), &opts),
+            Some(_) => {}
+        },
+        Command::Check | Command::Build | Command::Run => match opts.path.as_deref() {
+            None if opts.watch => watch_project(Path::new(""), &opts),
+            None => compile_project(Path::new(""), &opts),
+            Some(dir) if Path::new(dir).is_dir() && opts.watch => watch_project(Path::new(dir), &opts),
+            Some(dir) if Path::new(dir).is_dir() => compile_project(Path::new(dir), &opts),
+            Some(path) if opts.watch => watch_file(path, &opts),
+            Some(_) => {}
+        },
+        _ => {}
+    }
+
+    // everything else works on a file, which Options::parse already made sure we've got
+    let path: &str = opts.path.as_deref().unwrap_or_default();
+    let mut sources: SourceMap = SourceMap::new();
+    let file: FileId = sources.add(path, read_file(path));
+    match opts.command {
+        Command::Fix => fix_file(sources, file, &opts),
+        Command::Fmt => fmt_file(sources, file, &opts),
+        Command::Doc => doc_file(&sources, file, &opts),
+        _ => compile(&sources, file, &opts),
     }
 }
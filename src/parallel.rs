@@ -0,0 +1,48 @@
+//! running independent work on a few threads. results always come back in the order the work was handed out,
+//! whichever thread got to it first, so nothing printed from them depends on scheduling
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread::{available_parallelism, scope},
+};
+
+/// how many threads to use when -j doesn't say, one per core
+pub fn default_jobs() -> usize {
+    available_parallelism().map_or(1, |n| n.get())
+}
+
+/// f(0) through f(count - 1) spread over up to `jobs` threads, handed back in index order.
+/// threads pull the next index when they're done with one, so a few big files don't hold everyone up
+pub fn map<T: Send>(jobs: usize, count: usize, f: impl Fn(usize) -> T + Sync) -> Vec<T> {
+    let jobs: usize = jobs.clamp(1, count.max(1));
+    if jobs == 1 {
+        return (0..count).map(f).collect();
+    }
+
+    let next: AtomicUsize = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<T>>> = Mutex::new((0..count).map(|_| None).collect());
+    scope(|s| {
+        for _ in 0..jobs {
+            s.spawn(|| {
+                loop {
+                    let i: usize = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= count {
+                        break;
+                    }
+                    let result: T = f(i);
+                    results.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(result);
+                }
+            });
+        }
+    });
+
+    // scope rethrows any panic from a worker, so getting here means every index got its result
+    results
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .map(|r| r.expect("every job ran"))
+        .collect()
+}
@@ -1,37 +1,49 @@
 // keeping this on until everything in the parser is done...
 #![allow(dead_code)]
 
-use core::fmt;
+use std::ops::Index;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Ident<'src>(pub &'src str);
+// spans carry which file they're in, so they live with the rest of the file bookkeeping
+pub use crate::source::Span;
 
-// make ident print as just the value inside
-impl<'src> fmt::Display for Ident<'src> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
-    }
-}
+// names are interned, so the ast doesn't borrow the source and comparing two names is comparing two numbers
+pub use crate::intern::Symbol;
+pub type Ident = Symbol;
 
-/// literals for all the types below
+/// literals for all the types below. these are owned instead of interned, the table never shrinks and every edit
+/// to a string in the lsp would be another entry in it forever
 #[derive(Debug, Clone, PartialEq)]
-pub enum Literal<'src> {
+pub enum Literal {
     // TODO: figure out if i should just do Int with a bit value at parse
     // or if i should just store seperate literals for each bit width
-    Int(&'src str),
-    Uint(&'src str),
+    Int(String),
+    Uint(String),
 
-    Float(&'src str),
-    Double(&'src str),
+    Float(String),
+    Double(String),
     Bool(bool),
-    Char(&'src str),
-    String(&'src str),
+    Char(String),
+    String(String),
     Unit,
 }
 
+/// a type annotation and where it was written (inferred types get an empty span where the annotation would go)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Type {
+    pub kind: TypeKind,
+    pub span: Span,
+}
+
+impl Type {
+    #[inline]
+    pub fn new(kind: TypeKind, span: Span) -> Self {
+        Type { kind, span }
+    }
+}
+
 /// all builtin types
 #[derive(Debug, Clone, PartialEq)]
-pub enum Type<'src> {
+pub enum TypeKind {
     // one byte
     I8,
     U8,
@@ -59,37 +71,37 @@ pub enum Type<'src> {
     Str,
 
     // user defined types (local to scope)
-    Ident(Ident<'src>),
+    Ident(Ident),
 
     /// `lib`, `std::io::File`, maybe others
-    Path(Vec<Ident<'src>>),
+    Path(Vec<Ident>),
 
     /// fixed size, dynamic type, immutable
-    Tuple(Vec<Type<'src>>),
+    Tuple(Vec<Type>),
 
     /// fixed size, static type, mutable
     Array {
-        typ: Box<Type<'src>>,
+        typ: Box<Type>,
         len: Option<u64>,
     },
 
     /// polish dictionary defines function as: "everyone knows what a function is"
     Func {
-        params: Vec<Type<'src>>,
-        ret: Box<Type<'src>>,
+        params: Vec<Type>,
+        ret: Box<Type>,
     },
 
     // if i add a borrow system
     // `&T` / `&mut T`
     // Ref {
     //     mutable: bool,
-    //     inner: Box<Type<'src>>,
+    //     inner: Box<Type>,
     // },
 
     // `*T` / `*mut T`
     // Ptr {
     //     mutable: bool,
-    //     inner: Box<Type<'src>>,
+    //     inner: Box<Type>,
     // },
 
     // type unlisted, or specifically marked as inferred
@@ -98,30 +110,30 @@ pub enum Type<'src> {
 
 /// a small list of everything that can be on the left hand side of an assignment
 #[derive(Debug, Clone, PartialEq)]
-pub enum LeftSide<'src> {
-    // plain idents
-    Var(Ident<'src>),
+pub enum LeftSide {
+    // plain idents (and where they sit)
+    Var(Ident, Span),
 
     // field (struct/obj.field)
     Field {
-        obj: Box<Expr<'src>>,
-        name: Ident<'src>,
+        obj: ExprId,
+        name: Ident,
     },
 
     // subscript (tuple/array[i] or [i..j]/[..i])
     Subscript {
-        obj: Box<Expr<'src>>,
-        sub: Subscript<'src>,
+        obj: ExprId,
+        sub: Subscript,
     },
 }
 
 /// array accesses should only be indexing or slicing
 #[derive(Debug, Clone, PartialEq)]
-pub enum Subscript<'src> {
-    Index(Box<Expr<'src>>),
+pub enum Subscript {
+    Index(ExprId),
     Range {
-        start: Option<Box<Expr<'src>>>,
-        end: Option<Box<Expr<'src>>>,
+        start: Option<ExprId>,
+        end: Option<ExprId>,
     },
 }
 
@@ -192,48 +204,104 @@ pub enum AssignOp {
     ShrEq,
 }
 
+/// an expression plus the span from its first token to its last
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    #[inline]
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Expr { kind, span }
+    }
+}
+
+/// where an expression sits in the Ast it was parsed into. only means anything to that Ast
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExprId(u32);
+
+/// a whole parsed file. every expression in it lives in one flat arena (children before their parents, in the
+/// order they finished parsing) and points at its children by ExprId. one allocation that grows instead of one per
+/// node, walking a tree stays in the same few cache lines, and dropping one is a single free
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Ast {
+    pub stmts: Vec<Stmt>,
+    pub exprs: Vec<Expr>,
+}
+
+impl Ast {
+    pub fn new() -> Self {
+        Ast::default()
+    }
+
+    /// move an expression into the arena
+    #[inline]
+    pub fn alloc(&mut self, expr: Expr) -> ExprId {
+        let id: ExprId = ExprId(self.exprs.len() as u32);
+        self.exprs.push(expr);
+        id
+    }
+
+    /// build an expression straight into the arena
+    #[inline]
+    pub fn expr(&mut self, kind: ExprKind, span: Span) -> ExprId {
+        self.alloc(Expr::new(kind, span))
+    }
+}
+
+impl Index<ExprId> for Ast {
+    type Output = Expr;
+
+    #[inline]
+    fn index(&self, id: ExprId) -> &Expr {
+        &self.exprs[id.0 as usize]
+    }
+}
+
 /// general expressions which will be recursively parsed using chumsky
-/// box anything recursive, as otherwise the enum will be infinite, and rust needs
-/// to know the size at compile time.
+/// anything recursive points at its children by ExprId (they live in the Ast, see below) instead of boxing them,
+/// otherwise the enum would be infinite and every node would be its own allocation
 /// TODO: implement constant expressions (ConstExpr) which are evaluated down to a fixed integer value
 #[derive(Debug, Clone, PartialEq)]
-pub enum Expr<'src> {
+pub enum ExprKind {
     // var names
-    Ident(Ident<'src>),
+    Ident(Ident),
 
     // literal values
-    Literal(Literal<'src>),
+    Literal(Literal),
 
     // assignments
     Assign {
         op: AssignOp,
-        lhs: LeftSide<'src>,
-        rhs: Box<Expr<'src>>,
+        lhs: LeftSide,
+        rhs: ExprId,
     },
 
     // unary operations
     Unary {
         op: UnaryOp,
-        expr: Box<Expr<'src>>,
+        expr: ExprId,
     },
 
     // binary ops
     Binary {
         op: BinOp,
-        lhs: Box<Expr<'src>>,
-        rhs: Box<Expr<'src>>,
+        lhs: ExprId,
+        rhs: ExprId,
     },
 
     // function call
     Call {
-        func: Box<Expr<'src>>,
-        args: Vec<Expr<'src>>,
+        func: ExprId,
+        args: Vec<ExprId>,
     },
 
     // field access (a.b)
     Field {
-        obj: Box<Expr<'src>>,
-        name: Ident<'src>,
+        obj: ExprId,
+        name: Ident,
         // TODO: figure out ->
         // whether this access is a pointer or not
         // ptr: bool,
@@ -245,66 +313,82 @@ pub enum Expr<'src> {
     // method calls (a.b(); a.b().c(); a().b() and etc.)
     Method {
         // receiver isnt always just an obj, it can be a chained call
-        receiver: Box<Expr<'src>>,
-        method: Ident<'src>,
-        args: Vec<Expr<'src>>,
+        receiver: ExprId,
+        method: Ident,
+        args: Vec<ExprId>,
     },
 
     // index or slice (a[b] or a[b..c])
     Index {
-        obj: Box<Expr<'src>>,
-        sub: Subscript<'src>,
+        obj: ExprId,
+        sub: Subscript,
     },
 
     // control flow
     If {
-        cond: Box<Expr<'src>>,
-        then: Vec<Stmt<'src>>,
-        else_: Option<Vec<Stmt<'src>>>,
+        cond: ExprId,
+        then: Vec<Stmt>,
+        else_: Option<Vec<Stmt>>,
     },
 
     While {
-        cond: Box<Expr<'src>>,
-        body: Box<Stmt<'src>>,
+        cond: ExprId,
+        body: Box<Stmt>,
     },
 
     Match {
-        item: Box<Expr<'src>>,
-        branches: Vec<Branch<'src>>,
+        item: ExprId,
+        branches: Vec<Branch>,
     },
 
     // name for enhanced for loops, will just be iter if not
     For {
-        name: &'src str,
-        iter: Box<Expr<'src>>,
-        body: Box<Stmt<'src>>,
+        name: Ident,
+        iter: ExprId,
+        body: Box<Stmt>,
     },
 
-    Unknown,
+    /// placeholder for an expression that failed to parse (the node span is whatever it covered).
+    /// later passes should skip over these instead of reporting more errors
+    Error,
+}
+
+/// a pattern and its span
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+impl Pattern {
+    #[inline]
+    pub fn new(kind: PatternKind, span: Span) -> Self {
+        Pattern { kind, span }
+    }
 }
 
 /// helper for the specific thing matched on a pattern match
 #[derive(Debug, Clone, PartialEq)]
-pub enum Pattern<'src> {
+pub enum PatternKind {
     /// wildcard/default match
     Wildcard,
 
     /// just a plain identifier which binds its value
-    Ident(&'src str),
+    Ident(Ident),
 
     /// literal value match
-    Literal(Literal<'src>),
+    Literal(Literal),
 
     // match multiple cases
-    Or(Vec<Pattern<'src>>),
+    Or(Vec<Pattern>),
 
     // interval matching (1..10 or similar)
     Range {
-        start: Option<Box<Expr<'src>>>,
-        end: Option<Box<Expr<'src>>>,
+        start: Option<ExprId>,
+        end: Option<ExprId>,
     },
     // shit i have to add later
-    // Tuple(Vec<Pattern<'src>>),
+    // Tuple(Vec<Pattern>),
     // Array
     //
     // will prolly expand but for rn this is ok
@@ -312,27 +396,42 @@ pub enum Pattern<'src> {
 
 /// each branch of a match statement
 #[derive(Debug, Clone, PartialEq)]
-pub struct Branch<'src> {
-    pub pattern: Pattern<'src>,
-    pub guard: Option<Box<Expr<'src>>>,
-    pub body: Stmt<'src>,
+pub struct Branch {
+    pub pattern: Pattern,
+    pub guard: Option<ExprId>,
+    pub body: Stmt,
+}
+
+/// a statement and its span (not including the newline or semicolon ending it)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+impl Stmt {
+    #[inline]
+    pub fn new(kind: StmtKind, span: Span) -> Self {
+        Stmt { kind, span }
+    }
 }
 
 /// all types of statement. either control or a normal expression
 #[derive(Debug, Clone, PartialEq)]
-pub enum Stmt<'src> {
-    Expr(Expr<'src>),
+pub enum StmtKind {
+    Expr(ExprId),
 
     // control flow
-    Return(Option<Expr<'src>>),
+    Return(Option<ExprId>),
     Break,
     Continue,
 
     // variable declaration is a statement rather than an expression
     VarDecl {
-        name: Ident<'src>,
-        typ: Type<'src>,
-        init: Option<Expr<'src>>,
+        name: Ident,
+        name_span: Span,
+        typ: Type,
+        init: Option<ExprId>,
 
         // may drop this, but adding immutability for like tuples
         // forces a reassignment to change so may keep this as it has its purpose
@@ -341,5 +440,18 @@ pub enum Stmt<'src> {
 
         // global == static
         global: bool,
+
+        // any `///` or `/** */` comments right above it, markers and all (owned, same reason as literals)
+        doc: Vec<String>,
     },
+
+    // `import a::b` or `from a::b import c, d`. a plain import brings in no names (just the dependency)
+    Import {
+        module: Vec<Ident>,
+        module_span: Span,
+        names: Vec<(Ident, Span)>,
+    },
+
+    /// placeholder for a statement that failed to parse (same deal as ExprKind::Error)
+    Error,
 }
@@ -0,0 +1,556 @@
+//! printing the tree back out as stick source. everything prints as valid, parseable code with only the parentheses
+//! it actually needs, so `parse(x.to_string()) == x` (give or take spans). layout only, comments are the formatter's job
+use super::ast::*;
+use core::fmt;
+use std::fmt::{Display, Formatter};
+
+const INDENT: &str = "    ";
+
+// how tightly each kind of expression binds, same numbers parse_expr uses. anything binding looser than
+// what its parent needs gets parenthesized, everything else goes out bare
+pub const ASSIGN: u8 = 0;
+pub const UNARY: u8 = 12;
+pub const POSTFIX: u8 = 15;
+pub const ATOM: u8 = 16;
+
+impl BinOp {
+    /// the operator as written, and how tightly it binds
+    pub fn info(self) -> (&'static str, u8) {
+        match self {
+            BinOp::Or => ("or", 1),
+            BinOp::And => ("and", 2),
+            BinOp::BitOr => ("|", 3),
+            BinOp::BitXor => ("^", 4),
+            BinOp::BitAnd => ("&", 5),
+            BinOp::Eq => ("==", 6),
+            BinOp::NotEq => ("!=", 6),
+            BinOp::Less => ("<", 7),
+            BinOp::LessEq => ("<=", 7),
+            BinOp::Greater => (">", 7),
+            BinOp::GreaterEq => (">=", 7),
+            BinOp::Shl => ("<<", 8),
+            BinOp::Shr => (">>", 8),
+            BinOp::Add => ("+", 9),
+            BinOp::Sub => ("-", 9),
+            BinOp::Mul => ("*", 10),
+            BinOp::Div => ("/", 10),
+            BinOp::Mod => ("%", 10),
+            BinOp::Power => ("**", 11),
+        }
+    }
+}
+
+impl Display for BinOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.info().0)
+    }
+}
+
+impl Display for AssignOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AssignOp::Assign => "=",
+            AssignOp::PlusEq => "+=",
+            AssignOp::MinusEq => "-=",
+            AssignOp::StarEq => "*=",
+            AssignOp::SlashEq => "/=",
+            AssignOp::PercentEq => "%=",
+            AssignOp::AndEq => "&=",
+            AssignOp::OrEq => "|=",
+            AssignOp::XorEq => "^=",
+            AssignOp::ShlEq => "<<=",
+            AssignOp::ShrEq => ">>=",
+        })
+    }
+}
+
+// `not` is a word so it needs the space, the symbols don't
+impl Display for UnaryOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            UnaryOp::Neg => "-",
+            UnaryOp::Not => "not ",
+            UnaryOp::BitNot => "~",
+        })
+    }
+}
+
+impl Expr {
+    /// how tightly this expression binds (see the consts up top)
+    pub fn precedence(&self) -> u8 {
+        match &self.kind {
+            ExprKind::Assign { .. } => ASSIGN,
+            ExprKind::Binary { op, .. } => op.info().1,
+            ExprKind::Unary { .. } => UNARY,
+            ExprKind::Call { .. } | ExprKind::Field { .. } | ExprKind::Method { .. } | ExprKind::Index { .. } => POSTFIX,
+
+            // control flow is only ever safe bare at the top of an expression
+            ExprKind::If { .. } | ExprKind::While { .. } | ExprKind::Match { .. } | ExprKind::For { .. } => ASSIGN,
+            ExprKind::Ident(_) | ExprKind::Literal(_) | ExprKind::Error => ATOM,
+        }
+    }
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Int(s)
+            | Literal::Uint(s)
+            | Literal::Float(s)
+            | Literal::Double(s)
+            | Literal::Char(s)
+            | Literal::String(s) => f.write_str(s),
+            Literal::Bool(b) => write!(f, "{b}"),
+            Literal::Unit => f.write_str("()"),
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name: &str = match &self.kind {
+            TypeKind::I8 => "i8",
+            TypeKind::U8 => "u8",
+            TypeKind::Bool => "bool",
+            TypeKind::Char => "char",
+            TypeKind::I16 => "i16",
+            TypeKind::U16 => "u16",
+            TypeKind::I32 => "i32",
+            TypeKind::U32 => "u32",
+            TypeKind::F32 => "f32",
+            TypeKind::I64 => "i64",
+            TypeKind::U64 => "u64",
+            TypeKind::F64 => "f64",
+            TypeKind::Unit => "()",
+            TypeKind::Str => "str",
+            TypeKind::Inferred => "_",
+            TypeKind::Ident(name) => name.as_str(),
+            TypeKind::Path(parts) => return list(f, parts, "::"),
+            TypeKind::Tuple(types) => {
+                f.write_str("(")?;
+                list(f, types, ", ")?;
+                return f.write_str(")");
+            }
+            TypeKind::Array { typ, len } => {
+                write!(f, "[{typ}")?;
+                if let Some(len) = len {
+                    write!(f, "; {len}")?;
+                }
+                return f.write_str("]");
+            }
+            TypeKind::Func { params, ret } => {
+                f.write_str("fn(")?;
+                list(f, params, ", ")?;
+                return write!(f, ") -> {ret}");
+            }
+        };
+        f.write_str(name)
+    }
+}
+
+/// a node and the tree it came from. children are ExprIds into the tree, so a node can't print on its own
+pub struct Shown<'a, T> {
+    ast: &'a Ast,
+    node: &'a T,
+}
+
+impl Ast {
+    /// something in this tree, ready to print (`ast.show(&stmt)`)
+    pub fn show<'a, T>(&'a self, node: &'a T) -> Shown<'a, T> {
+        Shown { ast: self, node }
+    }
+}
+
+impl Display for Shown<'_, ExprId> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        expr(f, self.ast, *self.node, ASSIGN, 0)
+    }
+}
+
+/// blocks inside come out indented four spaces a level, with no newline after the statement itself
+impl Display for Shown<'_, Stmt> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        stmt(f, self.ast, self.node, 0)
+    }
+}
+
+impl Display for Shown<'_, Pattern> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        pattern(f, self.ast, self.node)
+    }
+}
+
+fn indent(f: &mut Formatter<'_>, depth: usize) -> fmt::Result {
+    for _ in 0..depth {
+        f.write_str(INDENT)?;
+    }
+    Ok(())
+}
+
+// seperated, no trailing seperator
+fn list<T: Display>(f: &mut Formatter<'_>, items: &[T], sep: &str) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(sep)?;
+        }
+        write!(f, "{item}")?;
+    }
+    Ok(())
+}
+
+fn args(f: &mut Formatter<'_>, ast: &Ast, args: &[ExprId], depth: usize) -> fmt::Result {
+    f.write_str("(")?;
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        expr(f, ast, *arg, ASSIGN, depth)?;
+    }
+    f.write_str(")")
+}
+
+fn subscript(f: &mut Formatter<'_>, ast: &Ast, sub: &Subscript, depth: usize) -> fmt::Result {
+    f.write_str("[")?;
+    match sub {
+        Subscript::Index(idx) => expr(f, ast, *idx, ASSIGN, depth)?,
+        Subscript::Range { start, end } => {
+            if let Some(start) = start {
+                expr(f, ast, *start, ASSIGN, depth)?;
+            }
+            f.write_str("..")?;
+            if let Some(end) = end {
+                expr(f, ast, *end, ASSIGN, depth)?;
+            }
+        }
+    }
+    f.write_str("]")
+}
+
+// min is the loosest binding the parent can take without parens
+fn expr(f: &mut Formatter<'_>, ast: &Ast, id: ExprId, min: u8, depth: usize) -> fmt::Result {
+    let e: &Expr = &ast[id];
+    let parens: bool = e.precedence() < min;
+    if parens {
+        f.write_str("(")?;
+    }
+
+    match &e.kind {
+        ExprKind::Ident(name) => f.write_str(name.as_str())?,
+        ExprKind::Literal(lit) => write!(f, "{lit}")?,
+
+        // assignment is right associative, so the right side can be another one bare
+        ExprKind::Assign { op, lhs, rhs } => {
+            match lhs {
+                LeftSide::Var(name, _) => f.write_str(name.as_str())?,
+                LeftSide::Field { obj, name } => {
+                    expr(f, ast, *obj, POSTFIX, depth)?;
+                    write!(f, ".{name}")?;
+                }
+                LeftSide::Subscript { obj, sub } => {
+                    expr(f, ast, *obj, POSTFIX, depth)?;
+                    subscript(f, ast, sub, depth)?;
+                }
+            }
+            write!(f, " {op} ")?;
+            expr(f, ast, *rhs, ASSIGN, depth)?;
+        }
+
+        ExprKind::Unary { op, expr: operand } => {
+            write!(f, "{op}")?;
+            expr(f, ast, *operand, UNARY, depth)?;
+        }
+
+        // everything's left associative, so only the right side needs to bind tighter
+        ExprKind::Binary { op, lhs, rhs } => {
+            let prec: u8 = op.info().1;
+            expr(f, ast, *lhs, prec, depth)?;
+            write!(f, " {op} ")?;
+            expr(f, ast, *rhs, prec + 1, depth)?;
+        }
+
+        ExprKind::Call { func, args: a } => {
+            expr(f, ast, *func, POSTFIX, depth)?;
+            args(f, ast, a, depth)?;
+        }
+        ExprKind::Field { obj, name } => {
+            expr(f, ast, *obj, POSTFIX, depth)?;
+            write!(f, ".{name}")?;
+        }
+        ExprKind::Method { receiver, method, args: a } => {
+            expr(f, ast, *receiver, POSTFIX, depth)?;
+            write!(f, ".{method}")?;
+            args(f, ast, a, depth)?;
+        }
+        ExprKind::Index { obj, sub } => {
+            expr(f, ast, *obj, POSTFIX, depth)?;
+            subscript(f, ast, sub, depth)?;
+        }
+
+        // the parser doesn't build any of these yet, so the layout is a best guess at what the syntax will be
+        ExprKind::If { cond, then, else_ } => {
+            f.write_str("if ")?;
+            expr(f, ast, *cond, ASSIGN, depth)?;
+            block(f, ast, then, depth)?;
+            if let Some(else_) = else_ {
+                f.write_str(" else")?;
+                block(f, ast, else_, depth)?;
+            }
+        }
+        ExprKind::While { cond, body } => {
+            f.write_str("while ")?;
+            expr(f, ast, *cond, ASSIGN, depth)?;
+            block(f, ast, std::slice::from_ref(body), depth)?;
+        }
+        ExprKind::For { name, iter, body } => {
+            write!(f, "for {name} in ")?;
+            expr(f, ast, *iter, ASSIGN, depth)?;
+            block(f, ast, std::slice::from_ref(body), depth)?;
+        }
+        ExprKind::Match { item, branches } => {
+            f.write_str("match ")?;
+            expr(f, ast, *item, ASSIGN, depth)?;
+            f.write_str(" {\n")?;
+            for branch in branches {
+                indent(f, depth + 1)?;
+                pattern(f, ast, &branch.pattern)?;
+                if let Some(guard) = &branch.guard {
+                    f.write_str(" if ")?;
+                    expr(f, ast, *guard, ASSIGN, depth + 1)?;
+                }
+                f.write_str(" => ")?;
+                stmt(f, ast, &branch.body, depth + 1)?;
+                f.write_str("\n")?;
+            }
+            indent(f, depth)?;
+            f.write_str("}")?;
+        }
+
+        // no source to copy from here, so broken bits just get marked
+        ExprKind::Error => f.write_str("<error>")?,
+    }
+
+    if parens {
+        f.write_str(")")?;
+    }
+    Ok(())
+}
+
+fn pattern(f: &mut Formatter<'_>, ast: &Ast, p: &Pattern) -> fmt::Result {
+    match &p.kind {
+        PatternKind::Wildcard => f.write_str("_"),
+        PatternKind::Ident(name) => f.write_str(name.as_str()),
+        PatternKind::Literal(lit) => write!(f, "{lit}"),
+        PatternKind::Or(patterns) => {
+            for (i, p) in patterns.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(" | ")?;
+                }
+                pattern(f, ast, p)?;
+            }
+            Ok(())
+        }
+        PatternKind::Range { start, end } => {
+            if let Some(start) = start {
+                expr(f, ast, *start, POSTFIX, 0)?;
+            }
+            f.write_str("..")?;
+            if let Some(end) = end {
+                expr(f, ast, *end, POSTFIX, 0)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// ` {` then each statement on its own indented line then `}`
+fn block(f: &mut Formatter<'_>, ast: &Ast, stmts: &[Stmt], depth: usize) -> fmt::Result {
+    f.write_str(" {\n")?;
+    for s in stmts {
+        indent(f, depth + 1)?;
+        stmt(f, ast, s, depth + 1)?;
+        f.write_str("\n")?;
+    }
+    indent(f, depth)?;
+    f.write_str("}")
+}
+
+fn stmt(f: &mut Formatter<'_>, ast: &Ast, s: &Stmt, depth: usize) -> fmt::Result {
+    match &s.kind {
+        StmtKind::Expr(e) => expr(f, ast, *e, ASSIGN, depth),
+        StmtKind::Return(e) => {
+            f.write_str("return")?;
+            if let Some(e) = e {
+                f.write_str(" ")?;
+                expr(f, ast, *e, ASSIGN, depth)?;
+            }
+            Ok(())
+        }
+        StmtKind::Break => f.write_str("break"),
+        StmtKind::Continue => f.write_str("continue"),
+
+        // an explicit `: _` has a real span and stays, a left out annotation has an empty one and stays out.
+        // doc comments are comments, so they're left to whoever's printing those
+        StmtKind::VarDecl {
+            name,
+            typ,
+            init,
+            mutable,
+            constant,
+            global,
+            ..
+        } => {
+            f.write_str("let")?;
+            for (set, word) in [(*constant, " const"), (*global, " static"), (*mutable, " mutable")] {
+                if set {
+                    f.write_str(word)?;
+                }
+            }
+            write!(f, " {name}")?;
+            if typ.span.start != typ.span.end {
+                write!(f, ": {typ}")?;
+            }
+            if let Some(init) = init {
+                f.write_str(" = ")?;
+                expr(f, ast, *init, ASSIGN, depth)?;
+            }
+            Ok(())
+        }
+
+        StmtKind::Import { module, names, .. } => {
+            if !names.is_empty() {
+                f.write_str("from ")?;
+            } else {
+                f.write_str("import ")?;
+            }
+            list(f, module, "::")?;
+            for (i, (name, _)) in names.iter().enumerate() {
+                f.write_str(if i == 0 { " import " } else { ", " })?;
+                write!(f, "{name}")?;
+            }
+            Ok(())
+        }
+
+        StmtKind::Error => f.write_str("<error>"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cli::Flags, error::Severity, lexer::lex, parser::Parser, source::SourceMap};
+
+    fn parse(src: &str) -> Ast {
+        let mut sources: SourceMap = SourceMap::new();
+        let file = sources.add("test.stick", src);
+        let lexed = lex(sources.get(file), &Flags::QUIET).unwrap_or_else(|_| panic!("didn't lex"));
+        let (ast, errors) = Parser::new(sources.get(file), &lexed).parse(&Flags::QUIET);
+        let codes: Vec<&str> = errors.iter().filter(|d| d.severity == Severity::Error).map(|d| d.err.code()).collect();
+        assert!(codes.is_empty(), "{src:?} has errors: {codes:?}");
+        ast
+    }
+
+    fn print(ast: &Ast) -> String {
+        ast.stmts.iter().map(|s| ast.show(s).to_string()).collect::<Vec<String>>().join("\n")
+    }
+
+    // every expression fully parenthesized, so two trees with the same shape print the same no matter what
+    // Display thinks needs parens
+    fn shape(ast: &Ast, id: ExprId) -> String {
+        let e = |id: ExprId| shape(ast, id);
+        let sub = |s: &Subscript| match s {
+            Subscript::Index(i) => e(*i),
+            Subscript::Range { start, end } => {
+                format!("{}..{}", start.map(e).unwrap_or_default(), end.map(e).unwrap_or_default())
+            }
+        };
+        match &ast[id].kind {
+            ExprKind::Ident(name) => name.to_string(),
+            ExprKind::Literal(lit) => lit.to_string(),
+            ExprKind::Binary { op, lhs, rhs } => format!("({} {op} {})", e(*lhs), e(*rhs)),
+            ExprKind::Unary { op, expr } => format!("({op}{})", e(*expr)),
+            ExprKind::Assign { op, lhs, rhs } => {
+                let lhs: String = match lhs {
+                    LeftSide::Var(name, _) => name.to_string(),
+                    LeftSide::Field { obj, name } => format!("{}.{name}", e(*obj)),
+                    LeftSide::Subscript { obj, sub: s } => format!("{}[{}]", e(*obj), sub(s)),
+                };
+                format!("({lhs} {op} {})", e(*rhs))
+            }
+            ExprKind::Call { func, args } => {
+                format!("{}({})", e(*func), args.iter().map(|a| e(*a)).collect::<Vec<_>>().join(", "))
+            }
+            ExprKind::Method { receiver, method, args } => {
+                format!("{}.{method}({})", e(*receiver), args.iter().map(|a| e(*a)).collect::<Vec<_>>().join(", "))
+            }
+            ExprKind::Field { obj, name } => format!("{}.{name}", e(*obj)),
+            ExprKind::Index { obj, sub: s } => format!("{}[{}]", e(*obj), sub(s)),
+            other => format!("{other:?}"),
+        }
+    }
+
+    // the shape of every expression statement and initializer, in order
+    fn shapes(ast: &Ast) -> Vec<String> {
+        ast.stmts
+            .iter()
+            .filter_map(|s| match &s.kind {
+                StmtKind::Expr(e) | StmtKind::VarDecl { init: Some(e), .. } => Some(shape(ast, *e)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // prints as expected, and printing it again after a reparse changes nothing (text or tree)
+    fn check(src: &str, expected: &str) {
+        let ast: Ast = parse(src);
+        let printed: String = print(&ast);
+        assert_eq!(printed, expected, "printing {src:?}");
+
+        let again: Ast = parse(&printed);
+        assert_eq!(print(&again), printed, "reprinting {src:?}");
+        assert_eq!(shapes(&again), shapes(&ast), "reparsing {src:?}");
+    }
+
+    #[test]
+    fn only_needed_parens() {
+        check("x = (a + b) * c", "x = (a + b) * c");
+        check("x = a + (b * c)", "x = a + b * c");
+        check("x = (a - b) - c", "x = a - b - c");
+        check("x = a - (b - c)", "x = a - (b - c)");
+        check("x = ((((a))))", "x = a");
+        check("x = (a or b) and (c or d)", "x = (a or b) and (c or d)");
+        check("x = a | b & c == d < e << f + g * h ** i", "x = a | b & c == d < e << f + g * h ** i");
+        check("x = ((a | b) & c) ** 2", "x = ((a | b) & c) ** 2");
+    }
+
+    #[test]
+    fn unary_inside_binary() {
+        check("x = -a * b", "x = -a * b");
+        check("x = a * -b", "x = a * -b");
+        check("x = -(a * b)", "x = -(a * b)");
+        check("x = (-a) ** 2", "x = -a ** 2");
+        check("x = -(a ** 2)", "x = -(a ** 2)");
+        check("x = not (a and b)", "x = not (a and b)");
+        check("x = not a and b", "x = not a and b");
+        check("x = ~-~a", "x = ~-~a");
+    }
+
+    #[test]
+    fn postfix_and_assignment() {
+        check("x = (f)(a + b, -c).g[1 + 2]", "x = f(a + b, -c).g[1 + 2]");
+        check("x = (a + b).get(0)", "x = (a + b).get(0)");
+        check("x = (-a)[0]", "x = (-a)[0]");
+        check("x = xs[1..] + xs[..n] + xs[a..b]", "x = xs[1..] + xs[..n] + xs[a..b]");
+        check("x = y = 1", "x = y = 1");
+        check("a.b[i] += (c = 2)", "a.b[i] += c = 2");
+        check("x <<= 1", "x <<= 1");
+    }
+
+    #[test]
+    fn golden() {
+        let src: &str = "import   a::b\nfrom a::b import c,d\nlet const   x :i32=(1)\nlet static mutable y: a::B= -1;\n\
+                         let z : _ = 'c'\nlet mutable s: str\nlet w = \"hi\" ;let f: f64 = 1.5\nfoo ( x,y ,)\n";
+        let expected: &str = "import a::b\nfrom a::b import c, d\nlet const x: i32 = 1\nlet static mutable y: a::B = -1\n\
+                              let z: _ = 'c'\nlet mutable s: str\nlet w = \"hi\"\nlet f: f64 = 1.5\nfoo(x, y)";
+        check(src, expected);
+    }
+}
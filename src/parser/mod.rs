@@ -1,4 +1,6 @@
 pub mod ast;
+pub mod display;
 pub mod parse;
+pub mod serialize;
 
 pub use parse::Parser;
\ No newline at end of file
@@ -1,24 +1,51 @@
 #![allow(dead_code, unused_variables)]
 
 use core::fmt;
-use std::{ops::Range, process::exit, time::Instant, mem::take};
+use std::{ops::Range, time::Instant, mem::take};
 
 use super::ast::*;
-use crate::error::{Diagnostic, ParseError, SyntaxError};
-use crate::lexer::Token;
+use crate::error::{
+    Diagnostic, ParseError, Severity, SyntaxError, Warning,
+    suggest::{KEYWORDS, did_you_mean},
+};
+use crate::{
+    cli::Flags,
+    lexer::{Token, lex::Lexed},
+    intern::sym,
+    source::SourceFile,
+};
 
 // didn't tie parser lifetime to source
 pub struct Parser<'src, 't> {
-    pub path: &'src str,
+    pub file: &'src SourceFile,
     pub src: &'src str,
     pub tokens: &'t [Token<'src>],
     pub spans: &'t [Range<usize>],
+    pub docs: &'t [Range<usize>],
     pub pos: usize,
     pub fastfail: bool,
-    pub errors: Vec<Diagnostic<'t, 'src>>
+    pub errors: Vec<Diagnostic>,
+
+    // where every expression goes as it's finished
+    pub ast: Ast,
 }
 
 impl<'src, 't> Parser<'src, 't> {
+    /// a parser sitting at the start of whatever lex() made
+    pub fn new(file: &'src SourceFile, lexed: &'t Lexed<'src>) -> Self {
+        Parser {
+            file,
+            src: &file.src,
+            tokens: &lexed.tokens,
+            spans: &lexed.spans,
+            docs: &lexed.docs,
+            pos: 0,
+            fastfail: false,
+            errors: Vec::new(),
+            ast: Ast::new(),
+        }
+    }
+
     #[inline]
     fn cur(&self) -> Option<&'t Token<'src>> {
         self.tokens.get(self.pos)
@@ -35,25 +62,81 @@ impl<'src, 't> Parser<'src, 't> {
         tok == matched
     }
 
+    // span of the current token, or an empty one at the end of the file once we run out
     #[inline]
-    fn error(&mut self, err: SyntaxError<'src>) {
-        let diag: Diagnostic<'_, '_> = Diagnostic {
-            path: self.path,
-            src: self.src,
-
+    fn span(&self) -> Span {
+        match self.spans.get(self.pos) {
             // small copy whatever
-            span: self.spans[self.pos].clone(),
-            err,
+            Some(span) => self.file.span(span.clone()),
+            None => self.file.span(self.src.len()..self.src.len()),
+        }
+    }
+
+    // zero width span right after the last consumed token, aka where a missing token should have gone
+    #[inline]
+    fn expected_span(&self) -> Span {
+        let end: usize = match self.pos.checked_sub(1).and_then(|i| self.spans.get(i)) {
+            Some(prev) => prev.end,
+            None => 0,
         };
+        self.file.span(end..end)
+    }
+
+    // everything from start up to the end of the last consumed token (merging first to last token)
+    #[inline]
+    fn span_from(&self, start: usize) -> Span {
+        self.file.span(start..self.expected_span().end.max(start))
+    }
+
+    #[inline]
+    fn error(&mut self, err: SyntaxError) {
+        self.error_at(self.span(), err);
+    }
+
+    fn error_at(&mut self, span: Span, err: SyntaxError) {
+        let diag: Diagnostic = Diagnostic::new(span, err, Severity::Error);
+        self.push_error(diag);
+    }
 
-        if self.fastfail {
-            println!("{diag}");
-            exit(0);
+    // same thing, but with an edit that makes it go away (replace whatever's in fix with replacement)
+    fn error_fix(&mut self, span: Span, err: SyntaxError, fix: Span, replacement: &str, message: &str) {
+        let diag: Diagnostic =
+            Diagnostic::new(span, err, Severity::Error).with_fix(fix, replacement, message);
+        self.push_error(diag);
+    }
+
+    // with -ff only the first error counts, parse() stops after the statement it's in and the caller reports it
+    // like any other (same as the lexer does)
+    fn push_error(&mut self, diag: Diagnostic) {
+        if self.fastfail && self.failed() {
+            return;
         }
 
         self.errors.push(diag);
     }
 
+    // any actual errors so far (warnings don't count)
+    #[inline]
+    fn failed(&self) -> bool {
+        self.errors.iter().any(|d| d.severity == Severity::Error)
+    }
+
+    // warnings never fastfail, whether they matter at all is up to the lint levels later on
+    fn warn_at(&mut self, span: Span, warning: Warning) {
+        let diag: Diagnostic = self.warning(span, warning);
+        self.errors.push(diag);
+    }
+
+    fn warn_fix(&mut self, span: Span, warning: Warning, fix: Span, replacement: &str, message: &str) {
+        let diag: Diagnostic = self.warning(span, warning).with_fix(fix, replacement, message);
+        self.errors.push(diag);
+    }
+
+    #[inline]
+    fn warning(&self, span: Span, warning: Warning) -> Diagnostic {
+        Diagnostic::new(span, SyntaxError::Warning(warning), Severity::Warning)
+    }
+
     #[inline]
     fn expect<F>(&mut self, f: F) -> Option<&Token<'src>>
     where
@@ -82,23 +165,54 @@ impl<'src, 't> Parser<'src, 't> {
         Some(tok)
     }
 
+    // the doc comments sitting right on top of whatever starts at `start`. they can stack, but a blank line
+    // (or anything that isn't whitespace) between them and the item means they belong to something else,
+    // and so does one at the end of a line of code
+    fn docs_before(&self, start: usize) -> Vec<String> {
+        let src: &'src str = self.src;
+        let mut docs: Vec<String> = Vec::new();
+        let mut end: usize = start;
+        for doc in self.docs.iter().rev().skip_while(|d| d.start >= start) {
+            let gap: &str = &src[doc.end..end];
+            let line: &str = src[..doc.start].rsplit('\n').next().unwrap_or_default();
+            if !gap.trim().is_empty() || gap.matches('\n').count() > 1 || !line.trim().is_empty() {
+                break;
+            }
+            docs.push(src[doc.clone()].to_string());
+            end = doc.start;
+        }
+        docs.reverse();
+        docs
+    }
+
+    // skip to the next newline, semicolon or closing brace so one bad statement doesn't take the rest down with it
+    fn synchronize(&mut self) {
+        while let Some(tok) = self.cur() {
+            if matches!(tok, Token::Newline | Token::Semicolon | Token::RBrace) {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    // an expression that's going to be some other node's child, straight into the arena
+    #[inline]
+    fn parse_child(&mut self, min: u8) -> ExprId {
+        let expr: Expr = self.parse_expr(min);
+        self.ast.alloc(expr)
+    }
+
     // TODO: add plain ranges. val = 1..3
     // TODO 2: add dest and type based decls. decide i64 int = 1 or let int: i64 = 1 and const, global, maybe static too: const i64 int = 1 or let const int: i64 = 1
     // TODO 3: make semicolons OPTIONAL at the end of a line (or to end a statement)
     #[inline]
-    fn parse_expr(&mut self, min: u8) -> Expr<'src> {
+    fn parse_expr(&mut self, min: u8) -> Expr {
         // check for anything before
-        let mut left: Expr<'_> = self.parse_prefix();
+        let start: usize = self.span().start;
+        let mut left: Expr = self.parse_prefix();
 
         // get the token into scope
         while let Some(tok) = self.cur() {
-            let tok: &Token<'_> = match self.cur() {
-                Some(tok) => tok,
-                None => {
-                    println!("not implemented: {tok:?}");
-                    return Expr::Unknown;
-                }
-            };
 
             // indexing/fields r highest precedence
             let precedence: u8 = match tok {
@@ -114,9 +228,10 @@ impl<'src, 't> Parser<'src, 't> {
                         self.advance();
 
                         // eat as many args as possible. default to take 8 before resizing then its ur problem lmao
-                        let mut args: Vec<Expr<'_>> = Vec::with_capacity(8);
+                        let errors: usize = self.errors.len();
+                        let mut args: Vec<ExprId> = Vec::with_capacity(8);
                         if !self.matches(&Token::RParen) {
-                            args.push(self.parse_expr(0));
+                            args.push(self.parse_child(0));
 
                             // match commas (and ending parenthesis)
                             while self.matches(&Token::Comma) {
@@ -126,34 +241,44 @@ impl<'src, 't> Parser<'src, 't> {
                                 }
 
                                 // evaluate THEN push
-                                args.push(self.parse_expr(0));
+                                args.push(self.parse_child(0));
                             }
+                        }
 
-                            // malformed calls
-                            if !self.matches(&Token::RParen) {
-                                panic!(
-                                    "expected ',' or ')' in call. have to add this to the error system"
-                                );
-                            }
+                        // a broken argument already reported itself
+                        if self.errors.len() > errors {
+                            return Expr::new(ExprKind::Error, self.span_from(start));
                         }
 
-                        // expect r paren
-                        self.expect(|t: &Token<'_>| matches!(t, Token::RParen))
-                            .expect("missing ')'");
+                        // expect r paren, anything else is a malformed call
+                        if self.expect(|t: &Token<'_>| matches!(t, Token::RParen)).is_none() {
+                            self.error_at(
+                                self.expected_span(),
+                                SyntaxError::Parse(ParseError::UnclosedCall),
+                            );
+                            return Expr::new(ExprKind::Error, self.span_from(start));
+                        }
 
                         // method calls exist, so there's a match here
-                        left = match left {
-                            Expr::Field { obj, name } => Expr::Method {
-                                receiver: obj,
-                                method: name,
-                                args,
-                            },
-
-                            // also boxing to avoid infinite recursive eval
-                            other => Expr::Call {
-                                func: Box::new(other),
-                                args,
-                            },
+                        let span: Span = self.span_from(start);
+                        left = match left.kind {
+                            ExprKind::Field { obj, name } => Expr::new(
+                                ExprKind::Method {
+                                    receiver: obj,
+                                    method: name,
+                                    args,
+                                },
+                                span,
+                            ),
+
+                            // the callee goes in the arena like any other child
+                            other => Expr::new(
+                                ExprKind::Call {
+                                    func: self.ast.alloc(Expr::new(other, left.span)),
+                                    args,
+                                },
+                                span,
+                            ),
                         };
                     }
 
@@ -162,19 +287,25 @@ impl<'src, 't> Parser<'src, 't> {
                         self.advance();
 
                         // fields r simple just should be one identifier
-                        let name = match self.advance() {
-                            Some(Token::Identifier(name)) => name,
+                        let name = match self.expect(|t| matches!(t, Token::Identifier(_))) {
+                            Some(&Token::Identifier(name)) => name,
                             _ => {
-                                println!("not implemented: {tok:?}");
-                                return Expr::Unknown;
+                                self.error_at(
+                                    self.expected_span(),
+                                    SyntaxError::Parse(ParseError::MissingField),
+                                );
+                                return Expr::new(ExprKind::Error, self.span_from(start));
                             }
                         };
 
-                        let lvalue: Box<Expr<'_>> = Box::new(left);
-                        left = Expr::Field {
-                            obj: lvalue,
-                            name: Ident(name),
-                        };
+                        let lvalue: ExprId = self.ast.alloc(left);
+                        left = Expr::new(
+                            ExprKind::Field {
+                                obj: lvalue,
+                                name: Ident::intern(name),
+                            },
+                            self.span_from(start),
+                        );
                     }
 
                     // slices/index
@@ -182,12 +313,12 @@ impl<'src, 't> Parser<'src, 't> {
                         self.advance();
 
                         // slices are denoted [start..end], [start..] or [..end]
-                        let sub: Subscript<'_> = if self.matches(&Token::DotDot) {
+                        let sub: Subscript = if self.matches(&Token::DotDot) {
                             self.advance();
 
                             // match the end bracket or error
-                            let end: Option<Box<Expr<'_>>> = if !self.matches(&Token::RBracket) {
-                                Some(Box::new(self.parse_expr(0)))
+                            let end: Option<ExprId> = if !self.matches(&Token::RBracket) {
+                                Some(self.parse_child(0))
                             } else {
                                 None
                             };
@@ -195,35 +326,40 @@ impl<'src, 't> Parser<'src, 't> {
                             Subscript::Range { start: None, end }
                         } else {
                             // otherwise try and evaluate out whatever is inside, start then end
-                            let start: Expr<'_> = self.parse_expr(0);
+                            let start: Expr = self.parse_expr(0);
                             if self.matches(&Token::DotDot) {
                                 self.advance();
 
                                 // if nothing matches its [i..]
-                                let end: Option<Box<Expr<'_>>> = if !self.matches(&Token::RBracket)
+                                let end: Option<ExprId> = if !self.matches(&Token::RBracket)
                                 {
-                                    Some(Box::new(self.parse_expr(0)))
+                                    Some(self.parse_child(0))
                                 } else {
                                     None
                                 };
 
                                 Subscript::Range {
-                                    start: Some(Box::new(start)),
+                                    start: Some(self.ast.alloc(start)),
                                     end,
                                 }
                             }
                             // NOW we know it's an index
                             else {
-                                Subscript::Index(Box::new(start))
+                                Subscript::Index(self.ast.alloc(start))
                             }
                         };
 
                         // expect an ending bracket
-                        self.expect(|t: &Token<'_>| matches!(t, Token::RBracket))
-                            .expect("missing ']'");
+                        if self.expect(|t: &Token<'_>| matches!(t, Token::RBracket)).is_none() {
+                            self.error_at(
+                                self.expected_span(),
+                                SyntaxError::Parse(ParseError::UnclosedIndex),
+                            );
+                            return Expr::new(ExprKind::Error, self.span_from(start));
+                        }
 
-                        let lvalue: Box<Expr<'_>> = Box::new(left);
-                        left = Expr::Index { obj: lvalue, sub };
+                        let lvalue: ExprId = self.ast.alloc(left);
+                        left = Expr::new(ExprKind::Index { obj: lvalue, sub }, self.span_from(start));
                     }
 
                     // never hits if this hits ur dumb
@@ -301,37 +437,49 @@ impl<'src, 't> Parser<'src, 't> {
             if op_prec < min {
                 break;
             }
+            let op_span: Span = self.span();
             self.advance();
 
             match op {
                 InfixKind::Assign(aop) => {
                     // assignments come last. otherwise left assoc
-                    let rhs: Expr<'_> = self.parse_expr(op_prec);
+                    let rhs: Expr = self.parse_expr(op_prec);
 
-                    let lhs = match left {
-                        Expr::Ident(ident) => LeftSide::Var(ident),
-                        Expr::Field { obj, name } => LeftSide::Field { obj, name },
-                        Expr::Index { obj, sub } => LeftSide::Subscript { obj, sub },
+                    let lhs = match left.kind {
+                        ExprKind::Ident(ident) => LeftSide::Var(ident, left.span),
+                        ExprKind::Field { obj, name } => LeftSide::Field { obj, name },
+                        ExprKind::Index { obj, sub } => LeftSide::Subscript { obj, sub },
                         _ => {
-                            println!("not implemented: or something went wrong {tok:?}");
-                            return Expr::Unknown;
+                            self.error_at(
+                                op_span,
+                                SyntaxError::Parse(ParseError::InvalidAssignment),
+                            );
+                            return Expr::new(ExprKind::Error, self.span_from(start));
                         }
                     };
 
-                    left = Expr::Assign {
-                        op: aop,
-                        lhs,
-                        rhs: Box::new(rhs),
-                    };
+                    let span: Span = left.span.to(rhs.span);
+                    left = Expr::new(
+                        ExprKind::Assign {
+                            op: aop,
+                            lhs,
+                            rhs: self.ast.alloc(rhs),
+                        },
+                        span,
+                    );
                 }
 
                 InfixKind::Binary(bop) => {
-                    let rhs: Expr<'_> = self.parse_expr(op_prec + 1);
-                    left = Expr::Binary {
-                        op: bop,
-                        lhs: Box::new(left),
-                        rhs: Box::new(rhs),
-                    };
+                    let rhs: Expr = self.parse_expr(op_prec + 1);
+                    let span: Span = left.span.to(rhs.span);
+                    left = Expr::new(
+                        ExprKind::Binary {
+                            op: bop,
+                            lhs: self.ast.alloc(left),
+                            rhs: self.ast.alloc(rhs),
+                        },
+                        span,
+                    );
                 }
             }
         }
@@ -340,138 +488,196 @@ impl<'src, 't> Parser<'src, 't> {
     }
 
     #[inline]
-    fn parse_prefix(&mut self) -> Expr<'src> {
-        // TODO: write proper error handling... and parse expr... and test this
-        let tok: &Token<'_> = self.advance().expect("unexpected EOF");
-        match tok {
-            Token::Minus => Expr::Unary {
+    fn parse_prefix(&mut self) -> Expr {
+        // running out of tokens mid expression
+        let Some(tok) = self.cur() else {
+            self.error_at(self.expected_span(), SyntaxError::Parse(ParseError::UnexpectedEof));
+            return Expr::new(ExprKind::Error, self.expected_span());
+        };
+
+        // nothing after an operator (left unconsumed so the statement still ends properly)
+        if matches!(tok, Token::Newline | Token::Semicolon) {
+            self.error_at(self.expected_span(), SyntaxError::Parse(ParseError::MissingOperand));
+            return Expr::new(ExprKind::Error, self.expected_span());
+        }
+
+        let span: Span = self.span();
+        self.advance();
+        let kind: ExprKind = match tok {
+            Token::Minus => ExprKind::Unary {
                 op: UnaryOp::Neg,
-                expr: Box::new(self.parse_expr(12)),
+                expr: self.parse_child(12),
             },
-            Token::LogicalNot => Expr::Unary {
+            Token::LogicalNot => ExprKind::Unary {
                 op: UnaryOp::Not,
-                expr: Box::new(self.parse_expr(12)),
+                expr: self.parse_child(12),
             },
-            Token::BitNot => Expr::Unary {
+            Token::BitNot => ExprKind::Unary {
                 op: UnaryOp::BitNot,
-                expr: Box::new(self.parse_expr(12)),
+                expr: self.parse_child(12),
             },
 
             Token::LParen => {
+                let errors: usize = self.errors.len();
                 let inner = self.parse_expr(0);
-                self.expect(|t: &Token<'_>| matches!(t, Token::RParen))
-                    .expect("missing ')'");
-                inner
+                if self.errors.len() > errors {
+                    return Expr::new(ExprKind::Error, self.span_from(span.start));
+                }
+                if self.expect(|t: &Token<'_>| matches!(t, Token::RParen)).is_none() {
+                    self.error_at(
+                        self.expected_span(),
+                        SyntaxError::Parse(ParseError::UnclosedParen),
+                    );
+                    return Expr::new(ExprKind::Error, self.span_from(span.start));
+                }
+
+                // widen the span to take the parens too
+                return Expr::new(inner.kind, self.span_from(span.start));
             }
 
-            Token::Identifier(name) => Expr::Ident(Ident(name)),
-            Token::LitInteger(n) => Expr::Literal(Literal::Int(n)),
-            Token::LitFloat(n) => Expr::Literal(Literal::Float(n)),
-            Token::LitString(s) => Expr::Literal(Literal::String(s)),
-            Token::LitChar(c) => Expr::Literal(Literal::Char(c)),
-            Token::Bool(b) => Expr::Literal(Literal::Bool(*b)),
+            Token::Identifier(name) => ExprKind::Ident(Ident::intern(name)),
+            Token::LitInteger(n) => ExprKind::Literal(Literal::Int(n.to_string())),
+            Token::LitFloat(n) => ExprKind::Literal(Literal::Float(n.to_string())),
+            Token::LitString(s) => ExprKind::Literal(Literal::String(s.to_string())),
+            Token::LitChar(c) => ExprKind::Literal(Literal::Char(c.to_string())),
+            Token::Bool(b) => ExprKind::Literal(Literal::Bool(*b)),
 
             Token::If => {
                 // self.advance();
                 // self.expect(|t: &Token<'_>| matches!(t, Token::LParen)).expect("missing '(' before start of condition");
-                // let cond: Expr<'_> = self.parse_expr(0);
+                // let cond: Expr = self.parse_expr(0);
                 // self.expect(|t: &Token<'_>| matches!(t, Token::RParen)).expect("missing ')'");
                 // Expr::If { cond: Box::new(cond), then: (), else_: () }
-                todo!("impl if statement")
+                self.error_at(span, SyntaxError::Parse(ParseError::Unsupported("if expressions")));
+                ExprKind::Error
             }
 
             // Token::While => self.parse_while_expr(),
             // Token::Match => self.parse_match_expr(),
             // Token::LBrace => self.parse_block_expr(),
 
-            // temporary solution for nimpl, i need to link ariadne
+            // anything else can't start an expression. step back so the caller can close whatever it had open
             _ => {
-                // add this back w a debug flag idk if debug { println!("not implemented: {tok:?}"); }
-                Expr::Unknown
+                self.pos -= 1;
+                self.error_at(
+                    span,
+                    SyntaxError::Parse(ParseError::UnexpectedToken(self.src[span.range()].to_string())),
+                );
+                ExprKind::Error
             }
-        }
+        };
+
+        Expr::new(kind, self.span_from(span.start))
     }
 
-    pub fn parse_let(&mut self) -> Result<Stmt<'src>, SyntaxError<'src>> {
+    // never fails outright, anything broken becomes a StmtKind::Error (or an ExprKind::Error on the right hand side)
+    pub fn parse_let(&mut self) -> Stmt {
+        let start: usize = self.span().start;
+        let doc: Vec<String> = self.docs_before(start);
         self.advance();
 
-        // specifiers are evaluated in this order
+        // specifiers are evaluated in this order. the removal spans run up to whatever comes next so the space goes too
         let constant = self.expect(|t| matches!(t, Token::Const)).is_some();
+        let static_span: Span = self.span();
         let global = self.expect(|t| matches!(t, Token::Static)).is_some();
+        let mutable_span: Span = self.span();
         let mutable = self.expect(|t| matches!(t, Token::Mutable)).is_some();
+        let after: usize = self.span().start;
 
         // ensure constant isnt used where it can't be
         if constant && mutable {
-            self.error(
-                SyntaxError::Parse(ParseError::ConstDisallowed(
-                    "constant cannot be used in tandem with mutable.",
-                )),
+            self.error_fix(
+                mutable_span,
+                SyntaxError::Parse(ParseError::ConstMutable),
+                self.file.span(mutable_span.start..after),
+                "",
+                "remove `mutable`",
             );
         }
         if constant && global {
-            self.error(
-                SyntaxError::Parse(ParseError::ConstDisallowed(
-                    "constant cannot be used in tandem with static.",
-                )),
+            self.error_fix(
+                static_span,
+                SyntaxError::Parse(ParseError::ConstStatic),
+                self.file.span(static_span.start..mutable_span.start),
+                "",
+                "remove `static`",
             );
         }
 
         // consume name (TODO: add let _)
-        let name: Ident<'_> = match self.expect(|t| matches!(t, Token::Identifier(_))) {
-            Some(Token::Identifier(name)) => Ident(name),
+        let name_span: Span = self.span();
+        let name: Ident = match self.expect(|t| matches!(t, Token::Identifier(_))) {
+            Some(Token::Identifier(name)) => Ident::intern(name),
             _ => {
-                return Err(
-                    SyntaxError::Parse(ParseError::MissingExpected(
-                        "let must have an identifier afterwards",
-                    ))
-                )
-                // continue;
+                self.error_at(self.expected_span(), SyntaxError::Parse(ParseError::MissingName));
+                return Stmt::new(StmtKind::Error, self.span_from(start));
             }
         };
 
         // consume annotation
-        let typ: Type<'_> = if self.matches(&Token::Colon) {
+        let typ: Type = if self.matches(&Token::Colon) {
             self.advance();
 
             // TODO: add support for array and generic types
-            match self.expect(|t| {
+            let span: Span = self.span();
+            let kind: TypeKind = match self.expect(|t| {
                 matches!(t, Token::Identifier(_) | Token::Unit | Token::Underscore)
             }) {
-                Some(Token::Identifier(typname)) => match *typname {
-                    "i8" => Type::I8,
-                    "u8" => Type::U8,
-                    "i16" => Type::I16,
-                    "u16" => Type::U16,
-                    "i32" => Type::I32,
-                    "u32" => Type::U32,
-                    "i64" => Type::I64,
-                    "u64" => Type::U64,
-                    "f32" => Type::F32,
-                    "f64" => Type::F64,
-                    "bool" => Type::Bool,
-                    "char" => Type::Char,
-                    "str" => Type::Str,
-                    _ => Type::Ident(Ident(typname)),
+                Some(&Token::Identifier(typname)) => match Ident::intern(typname) {
+                    sym::I8 => TypeKind::I8,
+                    sym::U8 => TypeKind::U8,
+                    sym::I16 => TypeKind::I16,
+                    sym::U16 => TypeKind::U16,
+                    sym::I32 => TypeKind::I32,
+                    sym::U32 => TypeKind::U32,
+                    sym::I64 => TypeKind::I64,
+                    sym::U64 => TypeKind::U64,
+                    sym::F32 => TypeKind::F32,
+                    sym::F64 => TypeKind::F64,
+                    sym::BOOL => TypeKind::Bool,
+                    sym::CHAR => TypeKind::Char,
+                    sym::STR => TypeKind::Str,
+
+                    // `a::b::C` is a path, anything else is a plain name
+                    typname if self.matches(&Token::DoubleColon) => {
+                        let mut parts: Vec<Ident> = vec![typname];
+                        while self.matches(&Token::DoubleColon) {
+                            self.advance();
+                            match self.expect(|t| matches!(t, Token::Identifier(_))) {
+                                Some(Token::Identifier(part)) => parts.push(Ident::intern(part)),
+                                _ => {
+                                    self.error_at(
+                                        self.expected_span(),
+                                        SyntaxError::Parse(ParseError::MissingType),
+                                    );
+                                    return Stmt::new(StmtKind::Error, self.span_from(start));
+                                }
+                            }
+                        }
+                        TypeKind::Path(parts)
+                    }
+                    typname => TypeKind::Ident(typname),
                 },
 
                 // unit type and inferred have to be handled seperately
-                Some(Token::Unit) => Type::Unit,
-                Some(Token::Underscore) => Type::Inferred,
+                Some(Token::Unit) => TypeKind::Unit,
+                Some(Token::Underscore) => TypeKind::Inferred,
 
                 // push missing type after :
                 _ => {
-                    return Err(
-                        SyntaxError::Parse(ParseError::MissingExpected(
-                            "expected type name after ':'",
-                        )),
+                    self.error_at(
+                        self.expected_span(),
+                        SyntaxError::Parse(ParseError::MissingType),
                     );
+                    return Stmt::new(StmtKind::Error, self.span_from(start));
                 }
-            }
+            };
+            Type::new(kind, self.span_from(span.start))
         }
 
         // (if no annotation the type is inferred by the compiler)
         else {
-            Type::Inferred
+            Type::new(TypeKind::Inferred, self.expected_span())
         };
 
         // expected equals to get to right hand of assignment (if none it's a decl)
@@ -480,55 +686,121 @@ impl<'src, 't> Parser<'src, 't> {
             self.advance();
 
             match self.cur().unwrap_or(&Token::Error) {
+                // keep the decl, just with a hole where the value goes
                 Token::Error | Token::Newline | Token::Semicolon => {
-                    return Err(
-                        SyntaxError::Parse(ParseError::MissingExpected(
-                            "expected expression after '='",
-                        )),
+                    self.error_at(
+                        self.expected_span(),
+                        SyntaxError::Parse(ParseError::MissingValue),
                     );
+                    init = Some(self.ast.expr(ExprKind::Error, self.expected_span()));
                 }
 
                 _ => {
-                    init = Some(self.parse_expr(0));
+                    init = Some(self.parse_child(0));
                 }
             }
         }
 
         // can't automatically deduce type on assignment (maybe make it so that the type is filled when assigned to?)
-        if typ == Type::Inferred && init.is_none() {
-            return Err(
-                SyntaxError::Parse(ParseError::MissingExpected(
-                    "type cannot be inferred without a right hand side",
-                )),
-            );
+        if typ.kind == TypeKind::Inferred && init.is_none() {
+            self.error_at(self.expected_span(), SyntaxError::Parse(ParseError::CannotInfer));
+            return Stmt::new(StmtKind::Error, self.span_from(start));
         }
 
         // we did it!!!!
-        Ok(
-            Stmt::VarDecl {
+        Stmt::new(
+            StmtKind::VarDecl {
                 name,
+                name_span,
                 typ,
                 init,
                 mutable,
                 constant,
                 global,
+                doc,
+            },
+            self.span_from(start),
+        )
+    }
+
+    // `import a::b` or `from a::b import c, d`
+    pub fn parse_import(&mut self) -> Stmt {
+        let start: usize = self.span().start;
+        let from: bool = self.matches(&Token::From);
+        self.advance();
+
+        // module path, names seperated by ::
+        let module_start: usize = self.span().start;
+        let mut module: Vec<Ident> = Vec::new();
+        loop {
+            match self.expect(|t| matches!(t, Token::Identifier(_))) {
+                Some(&Token::Identifier(part)) => module.push(Ident::intern(part)),
+                _ => {
+                    self.error_at(self.expected_span(), SyntaxError::Parse(ParseError::MissingModule));
+                    return Stmt::new(StmtKind::Error, self.span_from(start));
+                }
             }
+            if !self.matches(&Token::DoubleColon) {
+                break;
+            }
+            self.advance();
+        }
+        let module_span: Span = self.span_from(module_start);
+
+        // then whatever it's bringing in, if it's a from
+        let mut names: Vec<(Ident, Span)> = Vec::new();
+        if from {
+            if self.expect(|t| matches!(t, Token::Import)).is_none() {
+                self.error_at(self.expected_span(), SyntaxError::Parse(ParseError::MissingImport));
+                return Stmt::new(StmtKind::Error, self.span_from(start));
+            }
+            loop {
+                let span: Span = self.span();
+                match self.expect(|t| matches!(t, Token::Identifier(_))) {
+                    Some(&Token::Identifier(name)) => names.push((Ident::intern(name), span)),
+                    _ => {
+                        self.error_at(self.expected_span(), SyntaxError::Parse(ParseError::MissingImport));
+                        return Stmt::new(StmtKind::Error, self.span_from(start));
+                    }
+                }
+                if !self.matches(&Token::Comma) {
+                    break;
+                }
+                self.advance();
+            }
+        }
+
+        Stmt::new(
+            StmtKind::Import {
+                module,
+                module_span,
+                names,
+            },
+            self.span_from(start),
         )
     }
 
-    pub fn parse(&mut self, flags: &[bool]) -> Result<Vec<Stmt<'src>>, Vec<Diagnostic<'t, 'src>>> {
-        let mut nodes: Vec<Stmt<'src>> = Vec::new();
+    /// parses the whole token stream. always hands back a full tree (broken bits become Error nodes)
+    /// alongside every diagnostic found, so later passes can still run on files with syntax errors
+    pub fn parse(&mut self, flags: &Flags) -> (Ast, Vec<Diagnostic>) {
+        let mut nodes: Vec<Stmt> = Vec::new();
         let start: Instant = Instant::now();
 
         // resolve flags
-        let debug: bool = flags[0];
-        let fastfail: bool = flags[1];
+        let Flags { debug, fastfail, quiet, .. } = *flags;
         self.fastfail = fastfail;
         if debug {
             println!();
         }
 
         while let Some(cur) = self.cur() {
+            if self.fastfail && self.failed() {
+                break;
+            }
+
+            // any error inside the statement means we skip to the next one instead of cascading
+            let errors: usize = self.errors.len();
+
             match cur {
                 // skip newlines
                 Token::Newline => {
@@ -537,65 +809,338 @@ impl<'src, 't> Parser<'src, 't> {
                 }
 
                 // idents (read parse_ident)
-                Token::Identifier(_) => nodes.push(Stmt::Expr(self.parse_expr(0))),
+                Token::Identifier(_) => {
+                    let expr: Expr = self.parse_expr(0);
+                    let span: Span = expr.span;
+                    nodes.push(Stmt::new(StmtKind::Expr(self.ast.alloc(expr)), span));
+                }
 
                 // TODO: see how we can break some of this down
-                Token::Let => match self.parse_let() {
-                    Ok(stmt) => nodes.push(stmt),
-                    Err(e) => self.error(e),
-                }
+                Token::Let => nodes.push(self.parse_let()),
+                Token::Import | Token::From => nodes.push(self.parse_import()),
 
                 // control flow: this dont seem right but...
                 // Token::Break => nodes.push(Stmt::Break),
                 // Token::Continue => nodes.push(Stmt::Continue),
 
-                // TODO: wire this to the SyntaxError setup i alr have
+                // anything else can't start a statement (yet)
                 _ => {
-                    if debug {
-                        println!("not implemented: {cur}");
-                    }
+                    let span: Span = self.span();
+                    self.error(SyntaxError::Parse(ParseError::UnexpectedToken(self.src[span.range()].to_string())));
                     self.advance();
-                    continue;
+                    nodes.push(Stmt::new(StmtKind::Error, span));
                 }
             }
 
             if debug {
-                println!("Parsed: \n{:#?}\n", nodes.last().unwrap());
+                let node: &Stmt = nodes.last().unwrap();
+                println!("[bytes {:?}]: {}", node.span.range(), self.ast.show(node));
             }
 
-            // TODO: make the compiler warn on unnecessary semicolon
-            if !(self.matches(&Token::Newline) || self.matches(&Token::Semicolon)) {
-                self.error(
-                    SyntaxError::Parse(ParseError::MissingExpected(
-                        "all statements must be followed by either a newline or semicolon",
-                    )),
-                );
+            if self.errors.len() > errors {
+                self.synchronize();
+                while self.matches(&Token::Semicolon) || self.matches(&Token::RBrace) {
+                    self.advance();
+                }
                 continue;
             }
 
+            // the end of the file counts as a newline
+            if self.cur().is_some() && !(self.matches(&Token::Newline) || self.matches(&Token::Semicolon)) {
+                // a lone name followed by more stuff is usually a misspelled keyword (`retrun x`), splitting
+                // the line would just make it worse. resolution reports the name itself with the suggestion
+                let typo: Option<&str> = match nodes.last().map(|n| &n.kind) {
+                    Some(&StmtKind::Expr(expr)) => match self.ast[expr].kind {
+                        ExprKind::Ident(name) => did_you_mean(name.as_str(), KEYWORDS.iter().copied()),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+
+                if let Some(keyword) = typo {
+                    let diag: Diagnostic =
+                        Diagnostic::new(self.span(), SyntaxError::Parse(ParseError::MissingTerminator), Severity::Error)
+                            .with_help(format!("if this was meant to be `{keyword}`, fix the spelling instead"));
+                    self.push_error(diag);
+                } else {
+                    // swap the gap between the two statements for a newline, keeping the indentation of this line
+                    let gap: Span = self.file.span(self.expected_span().end..self.span().start);
+                    let line: &str = &self.src[self.src[..gap.start].rfind('\n').map_or(0, |i| i + 1)..];
+                    let indent: &str = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+                    self.error_fix(
+                        self.span(),
+                        SyntaxError::Parse(ParseError::MissingTerminator),
+                        gap,
+                        &format!("\n{indent}"),
+                        "start the next statement on a new line",
+                    );
+                }
+                self.synchronize();
+                continue;
+            }
+
+            // a semicolon only earns its keep when another statement follows it on the same line
             while self.matches(&Token::Semicolon) {
+                let span: Span = self.span();
+                let gap: usize = self.expected_span().end;
                 self.advance();
+                if matches!(self.cur(), None | Some(Token::Newline | Token::Semicolon)) {
+                    // any space before it goes too, nothing comes after it on this line anyway
+                    self.warn_fix(
+                        span,
+                        Warning::UnnecessarySemicolon,
+                        self.file.span(gap..span.end),
+                        "",
+                        "remove the `;`",
+                    );
+                }
             }
         }
 
-        println!(
-            "Parsed {} tokens into {} nodes. Took {}s.",
-            self.tokens.len(),
-            nodes.len(),
-            start.elapsed().as_secs_f64()
-        );
-
-        if self.errors.is_empty() {
-            Ok(nodes)
-        } else {
-            // i prolly dont have to do a move here... but wtv for rn
-            Err(take(&mut self.errors))
+        if !quiet {
+            println!(
+                "Parsed {} tokens into {} nodes. Took {}s.",
+                self.tokens.len(),
+                nodes.len(),
+                start.elapsed().as_secs_f64()
+            );
         }
+
+        // i prolly dont have to do a move here... but wtv for rn
+        self.ast.stmts = nodes;
+        (take(&mut self.ast), take(&mut self.errors))
     }
 }
 
+// where the parser's at, for debugging (the tree itself prints through the ast's Display impls)
 impl<'src, 't> fmt::Display for Parser<'src, 't> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!("have to do the display")
+        write!(f, "{}: token {} of {}", self.file.path, self.pos, self.tokens.len())?;
+        match self.cur() {
+            Some(tok) => write!(f, " (`{tok}` at bytes {:?})", self.span().range()),
+            None => f.write_str(" (end of file)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::lex, source::SourceMap};
+
+    fn parse_with(src: &str, flags: &Flags) -> (Ast, Vec<Diagnostic>) {
+        let mut sources: SourceMap = SourceMap::new();
+        let file = sources.add("test.stick", src);
+        let lexed: Lexed<'_> = lex(sources.get(file), &Flags::QUIET).unwrap_or_else(|_| panic!("didn't lex"));
+        Parser::new(sources.get(file), &lexed).parse(flags)
+    }
+
+    fn parse(src: &str) -> (Ast, Vec<Diagnostic>) {
+        parse_with(src, &Flags::QUIET)
+    }
+
+    fn codes(errors: &[Diagnostic]) -> Vec<&'static str> {
+        errors.iter().map(|d| d.err.code()).collect()
+    }
+
+    // the name each statement declares, or what it is otherwise
+    fn kinds(ast: &Ast) -> Vec<String> {
+        ast.stmts
+            .iter()
+            .map(|s| match &s.kind {
+                StmtKind::VarDecl { name, .. } => format!("let {name}"),
+                StmtKind::Expr(_) => "expr".to_string(),
+                StmtKind::Error => "error".to_string(),
+                other => format!("{other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_error_in_one_run() {
+        let (ast, errors) = parse("let = 1\nlet x: = 2\nlet y = )\nlet z = 4\nfoo(1 2\n");
+        assert_eq!(codes(&errors), ["E0010", "E0011", "E0017", "E0021"]);
+        assert_eq!(kinds(&ast), ["error", "error", "let y", "let z", "expr"]);
+    }
+
+    #[test]
+    fn recovers_at_a_semicolon() {
+        let (ast, errors) = parse("let = 1; let y = 2");
+        assert_eq!(codes(&errors), ["E0010"]);
+        assert_eq!(kinds(&ast), ["error", "let y"]);
+    }
+
+    #[test]
+    fn recovers_at_a_closing_brace() {
+        // the `}` can't start an expression, so the paren steps back off it and the statement syncs up to it
+        let (ast, errors) = parse("let a = (1 + } let b = 2");
+        assert_eq!(codes(&errors), ["E0017"]);
+        assert_eq!(errors[0].span.range(), 13..14);
+        assert_eq!(kinds(&ast), ["let a", "let b"]);
+    }
+
+    #[test]
+    fn stepping_back_leaves_the_token_for_the_next_statement() {
+        // the stray `)` is reported once, and the line after it still parses
+        let (ast, errors) = parse("let x = )\nx = 2");
+        assert_eq!(codes(&errors), ["E0017"]);
+        assert_eq!(kinds(&ast), ["let x", "expr"]);
+    }
+
+    #[test]
+    fn ends_at_the_end_of_the_file() {
+        // none of these can be finished, all of them have to stop anyway
+        for src in ["let x = (1 +", "let x =", "foo(", "foo(1,", "a.", "x[", "x[1..", "let x: a::", "from a import", "-"] {
+            let (_, errors) = parse(src);
+            assert!(!errors.is_empty(), "{src:?} parsed clean");
+        }
+        assert_eq!(codes(&parse("let x = 1 +").1), ["E0018"]);
+    }
+
+    #[test]
+    fn fastfail_stops_at_the_first_error() {
+        let flags: Flags = Flags {
+            fastfail: true,
+            ..Flags::QUIET
+        };
+        let (ast, errors) = parse_with("let = 1\nlet x: = 2\nlet y = )\n", &flags);
+        assert_eq!(codes(&errors), ["E0010"]);
+        assert_eq!(kinds(&ast), ["error"]);
+
+        // warnings before it don't count as the first error
+        let (_, errors) = parse_with("let a = 1;\nlet = 2\nlet = 3", &flags);
+        assert_eq!(codes(&errors), ["W0001", "E0010"]);
+    }
+
+    // the initializer of the nth statement, which has to be a let with one
+    fn init(ast: &Ast, n: usize) -> &Expr {
+        match &ast.stmts[n].kind {
+            StmtKind::VarDecl { init: Some(init), .. } => &ast[*init],
+            other => panic!("statement {n} is {other:?}"),
+        }
+    }
+
+    #[test]
+    fn broken_operand_becomes_an_error_node() {
+        let (ast, errors) = parse("let x = 1 +\nlet y = x * 2");
+        assert_eq!(codes(&errors), ["E0019"]);
+
+        // the let and the addition are still there, just with a hole on the right
+        let ExprKind::Binary { op: BinOp::Add, lhs, rhs } = init(&ast, 0).kind else {
+            panic!("not an addition: {:?}", init(&ast, 0));
+        };
+        assert_eq!(ast[lhs].kind, ExprKind::Literal(Literal::Int("1".to_string())));
+        assert_eq!(ast[rhs].kind, ExprKind::Error);
+
+        // and the next statement is untouched
+        assert!(matches!(init(&ast, 1).kind, ExprKind::Binary { op: BinOp::Mul, .. }));
+    }
+
+    #[test]
+    fn broken_initializer_keeps_the_declaration() {
+        let (ast, errors) = parse("let mutable x: i32 = )\nlet y =\nx = y");
+        assert_eq!(codes(&errors), ["E0017", "E0013"]);
+        assert_eq!(init(&ast, 0).kind, ExprKind::Error);
+        assert_eq!(init(&ast, 1).kind, ExprKind::Error);
+        match &ast.stmts[0].kind {
+            StmtKind::VarDecl { mutable, typ, .. } => {
+                assert!(mutable);
+                assert_eq!(typ.kind, TypeKind::I32);
+            }
+            other => panic!("not a let: {other:?}"),
+        }
+        assert!(matches!(&ast.stmts[2].kind, StmtKind::Expr(e) if matches!(ast[*e].kind, ExprKind::Assign { .. })));
+    }
+
+    #[test]
+    fn unusable_statements_become_error_statements() {
+        let (ast, errors) = parse("let x = 1\nlet = 2\n)\nlet y: = 3\nimport\nlet z = x");
+        assert_eq!(codes(&errors), ["E0010", "E0017", "E0011", "E0026"]);
+        assert_eq!(kinds(&ast), ["let x", "error", "error", "error", "error", "let z"]);
+    }
+
+    #[test]
+    fn broken_call_is_an_error_expression() {
+        let (ast, errors) = parse("foo(1 2)\nbar(3)");
+        assert_eq!(codes(&errors), ["E0021"]);
+        let exprs: Vec<&ExprKind> = ast
+            .stmts
+            .iter()
+            .map(|s| match s.kind {
+                StmtKind::Expr(e) => &ast[e].kind,
+                _ => panic!("not an expression statement"),
+            })
+            .collect();
+        assert_eq!(exprs[0], &ExprKind::Error);
+        assert!(matches!(exprs[1], ExprKind::Call { args, .. } if args.len() == 1));
+    }
+
+    #[test]
+    fn spans_cover_first_to_last_token() {
+        let (ast, _) = parse("let x: i32 = a + b * 2");
+        let StmtKind::VarDecl { name_span, typ, init: Some(init), .. } = &ast.stmts[0].kind else {
+            panic!("not a let");
+        };
+        assert_eq!(ast.stmts[0].span.range(), 0..22);
+        assert_eq!(name_span.range(), 4..5);
+        assert_eq!(typ.span.range(), 7..10);
+        assert_eq!(ast[*init].span.range(), 13..22);
+        let ExprKind::Binary { lhs, rhs, .. } = ast[*init].kind else {
+            panic!("not a binary");
+        };
+        assert_eq!(ast[lhs].span.range(), 13..14);
+        assert_eq!(ast[rhs].span.range(), 17..22);
+
+        // a left out annotation is an empty span where it would have gone
+        let (ast, _) = parse("let x = 1");
+        let StmtKind::VarDecl { typ, .. } = &ast.stmts[0].kind else {
+            panic!("not a let");
+        };
+        assert_eq!(typ.span.range(), 5..5);
+    }
+
+    #[test]
+    fn parens_and_postfix_spans() {
+        // the parens belong to the expression inside them
+        let (ast, _) = parse("let v = (a + b) * c");
+        let ExprKind::Binary { lhs, .. } = init(&ast, 0).kind else { panic!("not a binary") };
+        assert_eq!(init(&ast, 0).span.range(), 8..19);
+        assert_eq!(ast[lhs].span.range(), 8..15);
+
+        // each postfix widens the one before it
+        let (ast, _) = parse("foo(a, b).bar[1]");
+        let StmtKind::Expr(e) = ast.stmts[0].kind else { panic!("not an expression") };
+        let ExprKind::Index { obj: field, .. } = ast[e].kind else { panic!("not an index") };
+        let ExprKind::Field { obj: call, .. } = ast[field].kind else { panic!("not a field") };
+        assert_eq!(ast[e].span.range(), 0..16);
+        assert_eq!(ast[field].span.range(), 0..13);
+        assert_eq!(ast[call].span.range(), 0..9);
+    }
+
+    #[test]
+    fn import_spans() {
+        let (ast, _) = parse("from a::b import c, d");
+        let StmtKind::Import { module_span, names, .. } = &ast.stmts[0].kind else {
+            panic!("not an import");
+        };
+        assert_eq!(module_span.range(), 5..9);
+        assert_eq!(names.iter().map(|(_, span)| span.range()).collect::<Vec<_>>(), [17..18, 20..21]);
+    }
+
+    #[test]
+    fn missing_tokens_point_where_they_should_have_been() {
+        let at = |src: &str| -> Vec<(&'static str, Range<usize>)> {
+            parse(src).1.iter().map(|d| (d.err.code(), d.span.range())).collect()
+        };
+
+        // at the end of the file it's the very end, not the last token
+        assert_eq!(at("let x = 1 +"), [("E0018", 11..11)]);
+        assert_eq!(at("let x = (1"), [("E0022", 10..10)]);
+        assert_eq!(at("let x ="), [("E0013", 7..7)]);
+        assert_eq!(at("foo(1"), [("E0021", 5..5)]);
+
+        // before a newline it's right after the last token, not the newline or wherever the next line starts
+        assert_eq!(at("let x = 1 +   \nlet y = 2"), [("E0019", 11..11)]);
+        assert_eq!(at("let x:\nlet y = 2"), [("E0011", 6..6)]);
     }
 }
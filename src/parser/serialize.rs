@@ -0,0 +1,217 @@
+//! the tree as json, for `--emit=ast-json`. every node is an object with its `kind` first and its `span` last,
+//! operators and types go out as the source text they'd print as so nobody has to learn our enum names
+use super::ast::*;
+use crate::json::Json;
+
+impl Span {
+    pub fn to_json(self) -> Json {
+        vec![self.start, self.end].into()
+    }
+}
+
+// lists of nodes come up a lot
+fn all<T>(items: &[T], each: impl Fn(&T) -> Json) -> Json {
+    Json::Arr(items.iter().map(each).collect())
+}
+
+fn opt<T>(item: &Option<T>, each: impl Fn(&T) -> Json) -> Json {
+    item.as_ref().map_or(Json::Null, each)
+}
+
+// children are ids, so they get looked up and written out in place
+fn expr(ast: &Ast, id: ExprId) -> Json {
+    ast[id].to_json(ast)
+}
+
+impl Literal {
+    pub fn to_json(&self) -> Json {
+        let kind: &str = match self {
+            Literal::Int(_) => "Int",
+            Literal::Uint(_) => "Uint",
+            Literal::Float(_) => "Float",
+            Literal::Double(_) => "Double",
+            Literal::Bool(_) => "Bool",
+            Literal::Char(_) => "Char",
+            Literal::String(_) => "String",
+            Literal::Unit => "Unit",
+        };
+        Json::obj([("kind", kind.into()), ("value", self.to_string().into())])
+    }
+}
+
+impl Type {
+    pub fn to_json(&self) -> Json {
+        Json::obj([("type", self.to_string().into()), ("span", self.span.to_json())])
+    }
+}
+
+impl Pattern {
+    pub fn to_json(&self, ast: &Ast) -> Json {
+        let mut fields: Vec<(&str, Json)> = match &self.kind {
+            PatternKind::Wildcard => vec![("kind", "Wildcard".into())],
+            PatternKind::Ident(name) => vec![("kind", "Ident".into()), ("name", (*name).into())],
+            PatternKind::Literal(lit) => vec![("kind", "Literal".into()), ("value", lit.to_json())],
+            PatternKind::Or(patterns) => vec![("kind", "Or".into()), ("patterns", all(patterns, |p| p.to_json(ast)))],
+            PatternKind::Range { start, end } => vec![
+                ("kind", "Range".into()),
+                ("start", opt(start, |&e| expr(ast, e))),
+                ("end", opt(end, |&e| expr(ast, e))),
+            ],
+        };
+        fields.push(("span", self.span.to_json()));
+        Json::obj(fields)
+    }
+}
+
+impl Subscript {
+    pub fn to_json(&self, ast: &Ast) -> Json {
+        match self {
+            Subscript::Index(idx) => Json::obj([("kind", "Index".into()), ("index", expr(ast, *idx))]),
+            Subscript::Range { start, end } => Json::obj([
+                ("kind", "Range".into()),
+                ("start", opt(start, |&e| expr(ast, e))),
+                ("end", opt(end, |&e| expr(ast, e))),
+            ]),
+        }
+    }
+}
+
+impl LeftSide {
+    pub fn to_json(&self, ast: &Ast) -> Json {
+        match self {
+            LeftSide::Var(name, span) => {
+                Json::obj([("kind", "Var".into()), ("name", (*name).into()), ("span", span.to_json())])
+            }
+            LeftSide::Field { obj, name } => {
+                Json::obj([("kind", "Field".into()), ("obj", expr(ast, *obj)), ("name", (*name).into())])
+            }
+            LeftSide::Subscript { obj, sub } => {
+                Json::obj([("kind", "Subscript".into()), ("obj", expr(ast, *obj)), ("sub", sub.to_json(ast))])
+            }
+        }
+    }
+}
+
+impl Expr {
+    pub fn to_json(&self, ast: &Ast) -> Json {
+        let mut fields: Vec<(&str, Json)> = match &self.kind {
+            ExprKind::Ident(name) => vec![("kind", "Ident".into()), ("name", (*name).into())],
+            ExprKind::Literal(lit) => vec![("kind", "Literal".into()), ("value", lit.to_json())],
+            ExprKind::Assign { op, lhs, rhs } => vec![
+                ("kind", "Assign".into()),
+                ("op", op.to_string().into()),
+                ("lhs", lhs.to_json(ast)),
+                ("rhs", expr(ast, *rhs)),
+            ],
+            ExprKind::Unary { op, expr: operand } => vec![
+                ("kind", "Unary".into()),
+                ("op", op.to_string().trim_end().into()),
+                ("expr", expr(ast, *operand)),
+            ],
+            ExprKind::Binary { op, lhs, rhs } => vec![
+                ("kind", "Binary".into()),
+                ("op", op.to_string().into()),
+                ("lhs", expr(ast, *lhs)),
+                ("rhs", expr(ast, *rhs)),
+            ],
+            ExprKind::Call { func, args } => vec![
+                ("kind", "Call".into()),
+                ("func", expr(ast, *func)),
+                ("args", all(args, |&arg| expr(ast, arg))),
+            ],
+            ExprKind::Field { obj, name } => {
+                vec![("kind", "Field".into()), ("obj", expr(ast, *obj)), ("name", (*name).into())]
+            }
+            ExprKind::Method { receiver, method, args } => vec![
+                ("kind", "Method".into()),
+                ("receiver", expr(ast, *receiver)),
+                ("method", (*method).into()),
+                ("args", all(args, |&arg| expr(ast, arg))),
+            ],
+            ExprKind::Index { obj, sub } => {
+                vec![("kind", "Index".into()), ("obj", expr(ast, *obj)), ("sub", sub.to_json(ast))]
+            }
+            ExprKind::If { cond, then, else_ } => vec![
+                ("kind", "If".into()),
+                ("cond", expr(ast, *cond)),
+                ("then", all(then, |s| s.to_json(ast))),
+                ("else", opt(else_, |stmts| all(stmts, |s| s.to_json(ast)))),
+            ],
+            ExprKind::While { cond, body } => {
+                vec![("kind", "While".into()), ("cond", expr(ast, *cond)), ("body", body.to_json(ast))]
+            }
+            ExprKind::Match { item, branches } => vec![
+                ("kind", "Match".into()),
+                ("item", expr(ast, *item)),
+                (
+                    "branches",
+                    all(branches, |b| {
+                        Json::obj([
+                            ("pattern", b.pattern.to_json(ast)),
+                            ("guard", opt(&b.guard, |&g| expr(ast, g))),
+                            ("body", b.body.to_json(ast)),
+                        ])
+                    }),
+                ),
+            ],
+            ExprKind::For { name, iter, body } => vec![
+                ("kind", "For".into()),
+                ("name", (*name).into()),
+                ("iter", expr(ast, *iter)),
+                ("body", body.to_json(ast)),
+            ],
+            ExprKind::Error => vec![("kind", "Error".into())],
+        };
+        fields.push(("span", self.span.to_json()));
+        Json::obj(fields)
+    }
+}
+
+impl Stmt {
+    pub fn to_json(&self, ast: &Ast) -> Json {
+        let mut fields: Vec<(&str, Json)> = match &self.kind {
+            StmtKind::Expr(e) => vec![("kind", "Expr".into()), ("expr", expr(ast, *e))],
+            StmtKind::Return(e) => vec![("kind", "Return".into()), ("expr", opt(e, |&e| expr(ast, e)))],
+            StmtKind::Break => vec![("kind", "Break".into())],
+            StmtKind::Continue => vec![("kind", "Continue".into())],
+
+            // a left out annotation is null, an explicit `_` is a real type
+            StmtKind::VarDecl {
+                name,
+                name_span,
+                typ,
+                init,
+                mutable,
+                constant,
+                global,
+                doc,
+            } => vec![
+                ("kind", "VarDecl".into()),
+                ("name", (*name).into()),
+                ("name_span", name_span.to_json()),
+                ("type", (typ.span.start != typ.span.end).then(|| typ.to_json()).into()),
+                ("init", opt(init, |&init| expr(ast, init))),
+                ("mutable", (*mutable).into()),
+                ("constant", (*constant).into()),
+                ("global", (*global).into()),
+                ("doc", doc.clone().into()),
+            ],
+            StmtKind::Import {
+                module,
+                module_span,
+                names,
+            } => vec![
+                ("kind", "Import".into()),
+                ("module", module.iter().map(|p| p.as_str()).collect::<Vec<_>>().join("::").into()),
+                ("module_span", module_span.to_json()),
+                (
+                    "names",
+                    all(names, |(name, span)| Json::obj([("name", (*name).into()), ("span", span.to_json())])),
+                ),
+            ],
+            StmtKind::Error => vec![("kind", "Error".into())],
+        };
+        fields.push(("span", self.span.to_json()));
+        Json::obj(fields)
+    }
+}
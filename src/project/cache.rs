@@ -0,0 +1,158 @@
+//! the build cache, kept in target/ next to the root Stick.toml. every module remembers the hash of its source,
+//! what it imports and exports, and (if it checked clean) a hash of everything that check depended on. a module
+//! only gets lexed, parsed and resolved again when its source or the interface of something it imports changes.
+//! there's no bytecode to keep yet, it'll go in here too once there's a backend
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, read_to_string, write},
+    io,
+    path::{Path, PathBuf},
+};
+
+const CACHE: &str = "target/stick-cache";
+
+// anything written by a different compiler gets thrown out, what it means could have changed
+const HEADER: &str = concat!("stick-cache ", env!("CARGO_PKG_VERSION"));
+
+/// 64 bit FNV-1a. not cryptographic, just fast and good enough to notice a file changed
+#[derive(Debug, Clone, Copy)]
+pub struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf29ce484222325)
+    }
+}
+
+impl Fnv {
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    // strings end in a byte they can't contain so ["ab", "c"] and ["a", "bc"] don't come out the same
+    pub fn str(&mut self, s: &str) {
+        self.write(s.as_bytes());
+        self.write(&[0xff]);
+    }
+
+    pub fn finish(self) -> u64 {
+        self.0
+    }
+}
+
+/// hash one string
+pub fn fnv(s: &str) -> u64 {
+    let mut hasher: Fnv = Fnv::default();
+    hasher.write(s.as_bytes());
+    hasher.finish()
+}
+
+/// what's remembered about one module, keyed by its real path
+#[derive(Debug, Clone, Default)]
+pub struct Entry {
+    pub source: u64,
+    pub imports: Vec<String>,
+    pub exports: Vec<String>,
+
+    /// hash of the exported signatures, what other modules' checks depend on
+    pub interface: u64,
+
+    /// set when the module checked without a single diagnostic, to the fingerprint it was checked with
+    pub checked: Option<u64>,
+}
+
+/// how much got reused, for --debug
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stats {
+    pub scan_hits: usize,
+    pub scan_misses: usize,
+    pub check_hits: usize,
+    pub check_misses: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Cache {
+    pub entries: HashMap<String, Entry>,
+    pub stats: Stats,
+}
+
+impl Cache {
+    /// whatever's cached for the package in dir. a missing, unreadable or outdated cache is just an empty one
+    pub fn load(dir: &Path) -> Cache {
+        let mut cache: Cache = Cache::default();
+        let Ok(text) = read_to_string(dir.join(CACHE)) else {
+            return cache;
+        };
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER) {
+            return cache;
+        }
+
+        // one module a line: path, source, interface, checked (or -), imports, exports. the lists are space seperated
+        for line in lines {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [path, source, interface, checked, imports, exports] = fields[..] else {
+                return Cache::default();
+            };
+            let hex = |s: &str| u64::from_str_radix(s, 16).ok();
+            let (Some(source), Some(interface)) = (hex(source), hex(interface)) else {
+                return Cache::default();
+            };
+            let list = |s: &str| s.split(' ').filter(|s| !s.is_empty()).map(str::to_string).collect();
+            cache.entries.insert(
+                path.to_string(),
+                Entry {
+                    source,
+                    imports: list(imports),
+                    exports: list(exports),
+                    interface,
+                    checked: hex(checked),
+                },
+            );
+        }
+        cache
+    }
+
+    /// write the cache out, only keeping the modules in keep (anything else was deleted or isn't imported anymore)
+    pub fn save(&self, dir: &Path, keep: &[&str]) -> io::Result<()> {
+        let mut text: String = format!("{HEADER}\n");
+        for path in keep {
+            let Some(entry) = self.entries.get(*path) else {
+                continue;
+            };
+            let checked: String = entry.checked.map_or("-".to_string(), |c| format!("{c:016x}"));
+            text.push_str(&format!(
+                "{path}\t{:016x}\t{:016x}\t{checked}\t{}\t{}\n",
+                entry.source,
+                entry.interface,
+                entry.imports.join(" "),
+                entry.exports.join(" ")
+            ));
+        }
+
+        let path: PathBuf = dir.join(CACHE);
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        write(path, text)
+    }
+
+    /// whether a module already checked clean with this exact fingerprint, counting the hit or miss.
+    /// a miss forgets the old result, it only comes back if the module checks clean again
+    pub fn fresh(&mut self, key: &str, fingerprint: u64) -> bool {
+        let Some(entry) = self.entries.get_mut(key) else {
+            self.stats.check_misses += 1;
+            return false;
+        };
+        if entry.checked == Some(fingerprint) {
+            self.stats.check_hits += 1;
+            return true;
+        }
+        entry.checked = None;
+        self.stats.check_misses += 1;
+        false
+    }
+}
@@ -0,0 +1,465 @@
+//! Stick.toml: what a package is called, where its code lives, what it depends on and how it gets built
+use super::toml::{self, Entry, Section, Value};
+use crate::{
+    error::{Diagnostic, ManifestError, Severity, SyntaxError, suggest::did_you_mean},
+    source::SourceFile,
+};
+use std::{
+    env::current_dir,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+pub const MANIFEST: &str = "Stick.toml";
+
+// every section that means something, for suggestions (profiles can be called anything, these are the usual two)
+const SECTIONS: [&str; 4] = ["package", "dependencies", "profile.debug", "profile.release"];
+const PACKAGE_KEYS: [&str; 4] = ["name", "version", "entry", "src"];
+const PROFILE_KEYS: [&str; 2] = ["opt-level", "debug"];
+
+/// another package this one uses, by the name it's imported as
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    pub name: String,
+    pub dir: PathBuf,
+}
+
+/// how a build is done. `debug` is the default, `release` is what --release picks
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub opt_level: u8,
+    pub debug: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    /// the folder the Stick.toml is in, everything else is relative to it
+    pub dir: PathBuf,
+    pub name: String,
+    pub version: String,
+    pub entry: PathBuf,
+    pub src: Vec<PathBuf>,
+    pub dependencies: Vec<Dependency>,
+    pub profiles: Vec<Profile>,
+}
+
+/// walk up from a folder to the nearest one with a Stick.toml in it. the path comes back relative to the
+/// current folder when it's inside it, so errors point at `src/main.stick` and not the whole absolute path
+pub fn find(start: &Path) -> Option<PathBuf> {
+    let cwd: PathBuf = current_dir().ok()?;
+    let found: PathBuf = cwd.join(start).ancestors().map(|dir| dir.join(MANIFEST)).find(|path| path.is_file())?;
+    Some(found.strip_prefix(&cwd).map(Path::to_path_buf).unwrap_or(found))
+}
+
+/// the real location of a path, to tell whether two of them are the same file. "" is the current folder
+pub fn canonical(path: &Path) -> PathBuf {
+    let real: &Path = if path.as_os_str().is_empty() { Path::new(".") } else { path };
+    real.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl Manifest {
+    /// the profile with this name, if the manifest (or the defaults) have one
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    /// read a Stick.toml (its path is the Stick.toml itself). every problem gets reported, not just the first
+    pub fn parse(file: &SourceFile) -> Result<Manifest, Vec<Diagnostic>> {
+        let src: &str = &file.src;
+        let (sections, errors): (Vec<Section<'_>>, Vec<Diagnostic>) = toml::parse(file);
+        let dir: PathBuf = Path::new(&file.path).parent().map(Path::to_path_buf).unwrap_or_default();
+        let mut checker: Checker<'_> = Checker { file, dir, errors };
+
+        let mut manifest: Manifest = Manifest {
+            dir: checker.dir.clone(),
+            name: String::new(),
+            version: "0.1.0".to_string(),
+            entry: checker.dir.join("src/main.stick"),
+            src: vec![checker.dir.join("src")],
+            dependencies: Vec::new(),
+            profiles: vec![
+                Profile {
+                    name: "debug".to_string(),
+                    opt_level: 0,
+                    debug: true,
+                },
+                Profile {
+                    name: "release".to_string(),
+                    opt_level: 3,
+                    debug: false,
+                },
+            ],
+        };
+
+        let mut package: Option<Range<usize>> = None;
+        let mut named: bool = false;
+        let mut entry_span: Option<Range<usize>> = None;
+        let mut seen: Vec<&str> = Vec::new();
+        for section in &sections {
+            // the same section twice is the same mistake as the same key twice
+            if !section.name.is_empty() && seen.contains(&section.name) {
+                checker.error(section.span.clone(), ManifestError::DuplicateKey(section.name.to_string()), "already has a section above");
+                continue;
+            }
+            seen.push(section.name);
+
+            match section.name {
+                // keys up top, before any [section]
+                "" => {
+                    for entry in &section.entries {
+                        checker.error(entry.key_span.clone(), ManifestError::UnknownKey(entry.key.to_string()), "not in any section");
+                    }
+                }
+
+                "package" => {
+                    package = Some(section.span.clone());
+                    for entry in checker.unique(&section.entries, &PACKAGE_KEYS) {
+                        match entry.key {
+                            "name" => {
+                                named = true;
+                                if let Some(name) = checker.string(entry) {
+                                    manifest.name = name.to_string();
+                                }
+                            }
+                            "version" => {
+                                if let Some(version) = checker.string(entry) {
+                                    manifest.version = version.to_string();
+                                }
+                            }
+                            "entry" => {
+                                if let Some(entry_path) = checker.string(entry) {
+                                    manifest.entry = checker.dir.join(entry_path);
+                                    entry_span = Some(entry.value_span.clone());
+                                }
+                            }
+                            _ => match &entry.value {
+                                Value::Array(items) if items.iter().all(|(v, _)| matches!(v, Value::Str(_))) => {
+                                    manifest.src.clear();
+                                    for (item, span) in items {
+                                        if let Value::Str(dir) = item {
+                                            checker.exists(dir, span.clone(), "no such folder");
+                                            manifest.src.push(checker.dir.join(dir));
+                                        }
+                                    }
+                                }
+                                _ => checker.wrong_type(entry, "an array of strings"),
+                            },
+                        }
+                    }
+                }
+
+                // name = "../path" or name = { path = "../path" }
+                "dependencies" => {
+                    for entry in checker.unique(&section.entries, &[]) {
+                        let (dep, span): (&str, Range<usize>) = match &entry.value {
+                            Value::Str(dep) => (dep, entry.value_span.clone()),
+                            Value::Table(fields) => {
+                                let fields: Vec<&Entry<'_>> = checker.unique(fields, &["path"]);
+                                match fields.first().map(|f| (f, checker.string(f))) {
+                                    Some((field, Some(dep))) => (dep, field.value_span.clone()),
+                                    Some((_, None)) => continue,
+                                    None => {
+                                        checker.error(entry.value_span.clone(), ManifestError::MissingKey("path".to_string()), "needs a path");
+                                        continue;
+                                    }
+                                }
+                            }
+                            _ => {
+                                checker.wrong_type(entry, "a path or { path = \"...\" }");
+                                continue;
+                            }
+                        };
+
+                        // it's only a package if there's a manifest in there
+                        let dir: PathBuf = checker.dir.join(dep);
+                        if !dir.join(MANIFEST).is_file() {
+                            let message: &str = match dir.is_dir() {
+                                true => "no Stick.toml in this folder",
+                                false => "no such folder",
+                            };
+                            checker.error(span, ManifestError::MissingPath(dep.to_string()), message);
+                            continue;
+                        }
+                        manifest.dependencies.push(Dependency { name: entry.key.to_string(), dir });
+                    }
+                }
+
+                // [profile.<anything>], starting from the debug defaults unless it's overriding one that exists
+                name if name.starts_with("profile.") => {
+                    let name: &str = &name["profile.".len()..];
+                    let mut profile: Profile = manifest.profile(name).cloned().unwrap_or(Profile {
+                        name: name.to_string(),
+                        opt_level: 0,
+                        debug: true,
+                    });
+
+                    for entry in checker.unique(&section.entries, &PROFILE_KEYS) {
+                        match (entry.key, &entry.value) {
+                            ("opt-level", Value::Int(n @ 0..=3)) => profile.opt_level = *n as u8,
+                            ("opt-level", _) => checker.wrong_type(entry, "a number from 0 to 3"),
+                            (_, Value::Bool(b)) => profile.debug = *b,
+                            _ => checker.wrong_type(entry, "true or false"),
+                        }
+                    }
+
+                    manifest.profiles.retain(|p| p.name != name);
+                    manifest.profiles.push(profile);
+                }
+
+                name => {
+                    let mut diag: Diagnostic = checker
+                        .diagnostic(section.span.clone(), ManifestError::UnknownKey(name.to_string()))
+                        .with_primary("not a section");
+                    if let Some(s) = did_you_mean(name, SECTIONS) {
+                        diag = diag.with_suggestion(checker.file.span(section.span.clone()), format!("[{s}]"), format!("did you mean `[{s}]`?"));
+                    }
+                    checker.errors.push(diag);
+                }
+            }
+        }
+
+        // the name's the only thing without a default
+        let header: Range<usize> = package.clone().unwrap_or(0..0);
+        if !named {
+            let message: &str = match package {
+                Some(_) => "this package has no name",
+                None => "no [package] section",
+            };
+            checker.error(header.clone(), ManifestError::MissingKey("name".to_string()), message);
+        }
+
+        // the entry only gets checked once everything it's relative to is known to be right
+        if checker.errors.is_empty() && !manifest.entry.is_file() {
+            let (span, shown): (Range<usize>, &str) = match entry_span {
+                Some(span) => (span.clone(), &src[span.start + 1..span.end - 1]),
+                None => (header, "src/main.stick"),
+            };
+            checker.error(span, ManifestError::MissingPath(shown.to_string()), "the entry point isn't there");
+        }
+
+        checker.errors.sort_by_key(|d| d.span.start);
+        match checker.errors.is_empty() {
+            true => Ok(manifest),
+            false => Err(checker.errors),
+        }
+    }
+}
+
+// collects errors while the sections get read
+struct Checker<'src> {
+    file: &'src SourceFile,
+    dir: PathBuf,
+    errors: Vec<Diagnostic>,
+}
+
+impl<'src> Checker<'src> {
+    fn diagnostic(&self, span: Range<usize>, err: ManifestError) -> Diagnostic {
+        Diagnostic::new(self.file.span(span), SyntaxError::Manifest(err), Severity::Error)
+    }
+
+    fn error(&mut self, span: Range<usize>, err: ManifestError, primary: &str) {
+        let diag: Diagnostic = self.diagnostic(span, err).with_primary(primary);
+        self.errors.push(diag);
+    }
+
+    // the entries worth looking at: unknown keys (when there's a list of known ones) and repeats get reported and dropped
+    fn unique<'e>(&mut self, entries: &'e [Entry<'src>], known: &[&str]) -> Vec<&'e Entry<'src>> {
+        let mut kept: Vec<&Entry<'_>> = Vec::new();
+        for entry in entries {
+            if !known.is_empty() && !known.contains(&entry.key) {
+                let mut diag: Diagnostic =
+                    self.diagnostic(entry.key_span.clone(), ManifestError::UnknownKey(entry.key.to_string())).with_primary("not a key here");
+                if let Some(s) = did_you_mean(entry.key, known.iter().copied()) {
+                    diag = diag.with_suggestion(self.file.span(entry.key_span.clone()), s, format!("did you mean `{s}`?"));
+                }
+                self.errors.push(diag);
+            } else if let Some(first) = kept.iter().find(|e| e.key == entry.key) {
+                let diag: Diagnostic = self
+                    .diagnostic(entry.key_span.clone(), ManifestError::DuplicateKey(entry.key.to_string()))
+                    .with_primary("set again here")
+                    .with_label(self.file.span(first.key_span.clone()), "first set here");
+                self.errors.push(diag);
+            } else {
+                kept.push(entry);
+            }
+        }
+        kept
+    }
+
+    fn wrong_type(&mut self, entry: &Entry<'src>, expected: &str) {
+        let found: String = match entry.value {
+            Value::Int(n) => n.to_string(),
+            _ => entry.value.kind().to_string(),
+        };
+        let message: String = format!("expected {expected}, found {found}");
+        let diag: Diagnostic =
+            self.diagnostic(entry.value_span.clone(), ManifestError::WrongType(entry.key.to_string())).with_primary(message);
+        self.errors.push(diag);
+    }
+
+    fn string(&mut self, entry: &Entry<'src>) -> Option<&'src str> {
+        match entry.value {
+            Value::Str(s) => Some(s),
+            _ => {
+                self.wrong_type(entry, "a string");
+                None
+            }
+        }
+    }
+
+    fn exists(&mut self, path: &'src str, span: Range<usize>, primary: &str) {
+        if !self.dir.join(path).exists() {
+            self.error(span, ManifestError::MissingPath(path.to_string()), primary);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::SourceMap;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    // a throwaway package folder holding `files`, gone again once the test's done with it
+    struct Package(PathBuf);
+
+    impl Package {
+        fn new(name: &str, files: &[&str]) -> Package {
+            let dir: PathBuf = std::env::temp_dir().join(format!("stick-manifest-{}-{name}", std::process::id()));
+            let _ = remove_dir_all(&dir);
+            for file in files {
+                let path: PathBuf = dir.join(file);
+                create_dir_all(path.parent().unwrap()).unwrap();
+                write(path, "").unwrap();
+            }
+            create_dir_all(&dir).unwrap();
+            Package(dir)
+        }
+
+        // parse `toml` as this package's Stick.toml, handing back the manifest or the error codes
+        fn parse(&self, toml: &str) -> Result<Manifest, Vec<&'static str>> {
+            let mut sources: SourceMap = SourceMap::new();
+            let id = sources.add(self.0.join(MANIFEST).to_string_lossy(), toml);
+            Manifest::parse(sources.get(id)).map_err(|errors| errors.iter().map(|d| d.err.code()).collect())
+        }
+
+        // just the error codes, none if it parsed
+        fn errors(&self, toml: &str) -> Vec<&'static str> {
+            self.parse(toml).err().unwrap_or_default()
+        }
+    }
+
+    impl Drop for Package {
+        fn drop(&mut self) {
+            let _ = remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let package: Package = Package::new("defaults", &["src/main.stick"]);
+        let manifest: Manifest = package.parse("[package]\nname = \"demo\"\n").unwrap();
+        assert_eq!(manifest.name, "demo");
+        assert_eq!(manifest.version, "0.1.0");
+        assert_eq!(manifest.entry, package.0.join("src/main.stick"));
+        assert_eq!(manifest.src, [package.0.join("src")]);
+        assert_eq!(manifest.profile("debug").map(|p| (p.opt_level, p.debug)), Some((0, true)));
+        assert_eq!(manifest.profile("release").map(|p| (p.opt_level, p.debug)), Some((3, false)));
+    }
+
+    #[test]
+    fn everything_set() {
+        let package: Package = Package::new("everything", &["lib/start.stick", "dep/Stick.toml"]);
+        let manifest: Manifest = package
+            .parse(
+                "[package]\nname = \"demo\"\nversion = \"2.0.0\"\nentry = \"lib/start.stick\"\nsrc = [\"lib\"]\n\n\
+                 [dependencies]\nfirst = \"dep\"\nsecond = { path = \"dep\" }\n\n\
+                 [profile.release]\nopt-level = 2\n\n[profile.bench]\ndebug = false\n",
+            )
+            .unwrap();
+        assert_eq!(manifest.version, "2.0.0");
+        assert_eq!(manifest.entry, package.0.join("lib/start.stick"));
+        assert_eq!(manifest.src, [package.0.join("lib")]);
+        let deps: Vec<(&str, &Path)> = manifest.dependencies.iter().map(|d| (d.name.as_str(), d.dir.as_path())).collect();
+        let dep: PathBuf = package.0.join("dep");
+        assert_eq!(deps, [("first", dep.as_path()), ("second", dep.as_path())]);
+
+        // overriding a profile keeps what wasn't set, a new one starts from the debug defaults
+        assert_eq!(manifest.profile("release").map(|p| (p.opt_level, p.debug)), Some((2, false)));
+        assert_eq!(manifest.profile("bench").map(|p| (p.opt_level, p.debug)), Some((0, false)));
+    }
+
+    #[test]
+    fn malformed_and_invalid_values() {
+        let package: Package = Package::new("malformed", &["src/main.stick"]);
+        // errors come out in source order, and the missing name points at the [package] header
+        assert_eq!(package.errors("[package]\nname \"demo\"\n"), ["E0204", "E0200"]);
+        assert_eq!(package.errors("[package]\nname = demo\n"), ["E0204", "E0201"]);
+    }
+
+    #[test]
+    fn unknown_keys_and_sections() {
+        let package: Package = Package::new("unknown", &["src/main.stick"]);
+        assert_eq!(package.errors("name = \"demo\"\n[package]\nname = \"demo\"\n"), ["E0202"]);
+        assert_eq!(package.errors("[package]\nname = \"demo\"\nnmae = \"x\"\n"), ["E0202"]);
+        assert_eq!(package.errors("[package]\nname = \"demo\"\n[pakage]\n"), ["E0202"]);
+        assert_eq!(package.errors("[package]\nname = \"demo\"\n[profile.debug]\nlto = true\n"), ["E0202"]);
+    }
+
+    #[test]
+    fn unknown_names_get_suggestions() {
+        let package: Package = Package::new("suggest", &["src/main.stick"]);
+        let mut sources: SourceMap = SourceMap::new();
+        let id = sources.add(package.0.join(MANIFEST).to_string_lossy(), "[pakage]\nnmae = \"demo\"\n");
+        let errors: Vec<Diagnostic> = Manifest::parse(sources.get(id)).unwrap_err();
+        let fixes: Vec<&str> = errors.iter().flat_map(|d| &d.fixes).map(|f| f.replacement.as_str()).collect();
+        assert_eq!(fixes, ["[package]"]);
+    }
+
+    #[test]
+    fn duplicates() {
+        let package: Package = Package::new("duplicates", &["src/main.stick"]);
+        assert_eq!(package.errors("[package]\nname = \"a\"\nname = \"b\"\n"), ["E0203"]);
+        assert_eq!(package.errors("[package]\nname = \"a\"\n[package]\nversion = \"1\"\n"), ["E0203"]);
+
+        // the second one's the error, pointing back at the first
+        let mut sources: SourceMap = SourceMap::new();
+        let id = sources.add(package.0.join(MANIFEST).to_string_lossy(), "[package]\nname = \"a\"\nname = \"b\"\n");
+        let errors: Vec<Diagnostic> = Manifest::parse(sources.get(id)).unwrap_err();
+        assert_eq!(errors[0].span.range(), 21..25);
+        assert_eq!(errors[0].labels[0].span.range(), 10..14);
+    }
+
+    #[test]
+    fn missing_keys() {
+        let package: Package = Package::new("missing", &["src/main.stick", "dep/Stick.toml"]);
+        assert_eq!(package.errors(""), ["E0204"]);
+        assert_eq!(package.errors("[package]\nversion = \"1\"\n"), ["E0204"]);
+        assert_eq!(package.errors("[package]\nname = \"a\"\n[dependencies]\ndep = { }\n"), ["E0204"]);
+    }
+
+    #[test]
+    fn wrong_types() {
+        let package: Package = Package::new("types", &["src/main.stick"]);
+        assert_eq!(package.errors("[package]\nname = 1\n"), ["E0205"]);
+        assert_eq!(package.errors("[package]\nname = \"a\"\nsrc = \"src\"\n"), ["E0205"]);
+        assert_eq!(package.errors("[package]\nname = \"a\"\n[profile.debug]\nopt-level = 4\n"), ["E0205"]);
+        assert_eq!(package.errors("[package]\nname = \"a\"\n[profile.debug]\ndebug = 1\n"), ["E0205"]);
+        assert_eq!(package.errors("[package]\nname = \"a\"\n[dependencies]\ndep = true\n"), ["E0205"]);
+    }
+
+    #[test]
+    fn missing_paths() {
+        let package: Package = Package::new("paths", &["lib/other.stick", "empty/.keep"]);
+        assert_eq!(package.errors("[package]\nname = \"a\"\n"), ["E0206"]);
+        assert_eq!(package.errors("[package]\nname = \"a\"\nentry = \"lib/main.stick\"\n"), ["E0206"]);
+        assert_eq!(
+            package.errors("[package]\nname = \"a\"\nentry = \"lib/other.stick\"\nsrc = [\"nope\"]\n"), ["E0206"]
+        );
+
+        // a dependency has to be a folder with a Stick.toml in it
+        let deps: &str = "[package]\nname = \"a\"\nentry = \"lib/other.stick\"\n[dependencies]\nx = \"empty\"\ny = \"gone\"\n";
+        assert_eq!(package.errors(deps), ["E0206", "E0206"]);
+    }
+}
@@ -0,0 +1,50 @@
+//! projects: a folder with a Stick.toml, the modules in it and the packages it depends on
+pub mod cache;
+pub mod manifest;
+pub mod modules;
+pub mod toml;
+
+pub use manifest::{MANIFEST, Manifest, Profile, find};
+pub use modules::{Module, Project};
+
+use std::{
+    fs::{create_dir, create_dir_all, write},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// why a project couldn't be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    /// something couldn't be read (holds the message)
+    Io(String),
+
+    /// a Stick.toml had errors, they've already been reported
+    Manifest,
+}
+
+/// whether a name works as a package name (it has to be a bare toml key and an import path segment)
+pub fn valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// `stick new <name>`: a folder with a manifest, an entry point and a .gitignore for the build output.
+/// never touches a folder that's already there
+pub fn scaffold(name: &str) -> io::Result<PathBuf> {
+    let dir: &Path = Path::new(name);
+    create_dir(dir)?;
+    create_dir_all(dir.join("src"))?;
+
+    let manifest: String = format!(
+        "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nentry = \"src/main.stick\"\n\n[dependencies]\n# util = {{ path = \"../util\" }}\n"
+    );
+    write(dir.join(MANIFEST), manifest)?;
+    write(
+        dir.join("src/main.stick"),
+        "/// where it all starts. other files in src/ are modules, `from a::b import c` pulls c out of src/a/b.stick\nlet greeting = \"hello, world\"\n",
+    )?;
+    write(dir.join(".gitignore"), "target/\n")?;
+    Ok(dir.to_path_buf())
+}
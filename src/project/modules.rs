@@ -0,0 +1,318 @@
+//! finding every module in a project. the root package brings in everything under its source folders, then
+//! imports get followed (into dependencies too) until nothing new turns up. `a::b` is `<src>/a/b.stick` in
+//! the importing package, unless `a` is one of its dependencies, then it's `b` inside that package
+//! (and a plain `import a` is the dependency's entry point)
+use super::{
+    LoadError,
+    cache::{Cache, Entry, Fnv, fnv},
+    manifest::{MANIFEST, Manifest, canonical},
+};
+use crate::{
+    cli::Flags,
+    error::Diagnostic,
+    lexer::lex,
+    parser::{
+        Parser,
+        ast::{Ast, StmtKind},
+    },
+    sema::Exports,
+    source::{FileId, SourceFile, SourceMap},
+};
+use std::{
+    collections::HashMap,
+    fs::{read_dir, read_to_string},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// one .stick file and what it imports/exports
+#[derive(Debug, Clone)]
+pub struct Module {
+    /// its name inside its own package, `a::b` for src/a/b.stick
+    pub name: String,
+    pub package: usize,
+
+    /// where its path and source are, in the project's SourceMap
+    pub file: FileId,
+
+    /// its real path, what the cache knows it by
+    pub key: String,
+
+    /// every import as written, and the module it turned out to be (None if nothing matched)
+    pub imports: Vec<(String, Option<usize>)>,
+
+    /// the top level declarations
+    pub exports: Vec<String>,
+
+    /// hashes of the source and of what it exports
+    pub hash: u64,
+    pub interface: u64,
+}
+
+/// a package and everything it pulls in. packages[0] is the one being built
+#[derive(Debug)]
+pub struct Project {
+    pub packages: Vec<Manifest>,
+    pub modules: Vec<Module>,
+    pub cache: Cache,
+
+    /// every file read along the way, manifests included
+    pub sources: SourceMap,
+}
+
+/// where problems found while loading go, along with the files they point into
+pub type Report<'r> = &'r mut dyn FnMut(&SourceMap, &[Diagnostic]);
+
+// the contents of a file, into the SourceMap
+fn read(sources: &mut SourceMap, path: &Path) -> Result<FileId, LoadError> {
+    let path: String = path.to_string_lossy().into_owned();
+    let src: String = read_to_string(&path).map_err(|e| LoadError::Io(format!("failed to read {path}: {e}")))?;
+    Ok(sources.add(path, src))
+}
+
+// reads a manifest, handing any problems with it to report
+fn load_manifest(sources: &mut SourceMap, path: &Path, report: Report<'_>) -> Result<Manifest, LoadError> {
+    let file: FileId = read(sources, path)?;
+    Manifest::parse(sources.get(file)).map_err(|errors| {
+        report(sources, &errors);
+        LoadError::Manifest
+    })
+}
+
+// every .stick file under a folder, sorted so the order (and so the output) never depends on the filesystem
+fn stick_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            stick_files(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "stick") {
+            out.push(path);
+        }
+    }
+}
+
+impl Project {
+    /// load the package at this Stick.toml and find everything it's made of. manifest errors (in it or any
+    /// dependency) go to report as they're found, and then stop the load
+    pub fn load(manifest: &Path, report: Report<'_>) -> Result<Project, LoadError> {
+        let mut sources: SourceMap = SourceMap::new();
+        let root: Manifest = load_manifest(&mut sources, manifest, report)?;
+        let mut project: Project = Project {
+            packages: Vec::new(),
+            modules: Vec::new(),
+            cache: Cache::load(&root.dir),
+            sources,
+        };
+        let mut seen: HashMap<PathBuf, usize> = HashMap::new();
+
+        // the entry first, so it's always module 0, then the rest of the source folders
+        let mut files: Vec<PathBuf> = vec![root.entry.clone()];
+        for dir in &root.src {
+            stick_files(dir, &mut files);
+        }
+        project.packages.push(root);
+        for file in files {
+            project.add(0, &file, &mut seen)?;
+        }
+
+        // new modules get pushed onto the end as imports find them, so this runs until there's nothing left
+        let mut next: usize = 0;
+        while next < project.modules.len() {
+            let imports: Vec<String> = project.modules[next].imports.iter().map(|(m, _)| m.clone()).collect();
+            for (i, import) in imports.iter().enumerate() {
+                let found: Option<usize> = project.import(next, import, &mut seen, report)?;
+                project.modules[next].imports[i].1 = found;
+            }
+            next += 1;
+        }
+        Ok(project)
+    }
+
+    // the module at this path, reading it if it's new. it only gets scanned if the cache doesn't already know
+    // what's in this exact source
+    fn add(&mut self, package: usize, file: &Path, seen: &mut HashMap<PathBuf, usize>) -> Result<usize, LoadError> {
+        let real: PathBuf = canonical(file);
+        if let Some(&idx) = seen.get(&real) {
+            return Ok(idx);
+        }
+
+        let id: FileId = read(&mut self.sources, file)?;
+        let key: String = real.to_string_lossy().into_owned();
+        let hash: u64 = fnv(self.sources.src(id));
+        let entry: Entry = match self.cache.entries.get(&key) {
+            Some(entry) if entry.source == hash => {
+                self.cache.stats.scan_hits += 1;
+                entry.clone()
+            }
+            _ => {
+                self.cache.stats.scan_misses += 1;
+                let entry: Entry = scan(self.sources.get(id), hash);
+                self.cache.entries.insert(key.clone(), entry.clone());
+                entry
+            }
+        };
+
+        // named by where it sits in a source folder, or just by its file name if it's outside all of them
+        let manifest: &Manifest = &self.packages[package];
+        let relative: &Path = manifest
+            .src
+            .iter()
+            .find_map(|dir| file.strip_prefix(dir).ok())
+            .unwrap_or_else(|| Path::new(file.file_name().unwrap_or_default()));
+        let name: String = relative
+            .with_extension("")
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("::");
+
+        self.modules.push(Module {
+            name,
+            package,
+            file: id,
+            key,
+            imports: entry.imports.into_iter().map(|m| (m, None)).collect(),
+            exports: entry.exports,
+            hash,
+            interface: entry.interface,
+        });
+        seen.insert(real, self.modules.len() - 1);
+        Ok(self.modules.len() - 1)
+    }
+
+    // where an import in one module points, loading dependencies the first time something reaches into them
+    fn import(
+        &mut self,
+        from: usize,
+        import: &str,
+        seen: &mut HashMap<PathBuf, usize>,
+        report: Report<'_>,
+    ) -> Result<Option<usize>, LoadError> {
+        let package: usize = self.modules[from].package;
+        let (first, rest): (&str, Option<&str>) = match import.split_once("::") {
+            Some((first, rest)) => (first, Some(rest)),
+            None => (import, None),
+        };
+
+        let (package, rest): (usize, Option<&str>) =
+            match self.packages[package].dependencies.iter().find(|d| d.name == first) {
+                Some(dep) => {
+                    let dir: PathBuf = canonical(&dep.dir);
+                    let loaded: Option<usize> = self.packages.iter().position(|p| canonical(&p.dir) == dir);
+                    let idx: usize = match loaded {
+                        Some(idx) => idx,
+                        None => {
+                            let manifest: Manifest = load_manifest(&mut self.sources, &dep.dir.join(MANIFEST), report)?;
+                            self.packages.push(manifest);
+                            self.packages.len() - 1
+                        }
+                    };
+                    (idx, rest)
+                }
+                None => (package, Some(import)),
+            };
+
+        // a bare dependency name is its entry point
+        let Some(rest) = rest else {
+            let entry: PathBuf = self.packages[package].entry.clone();
+            return self.add(package, &entry, seen).map(Some);
+        };
+
+        let relative: PathBuf = rest.split("::").collect::<PathBuf>().with_extension("stick");
+        let file: Option<PathBuf> = self.packages[package].src.iter().map(|dir| dir.join(&relative)).find(|f| f.is_file());
+        match file {
+            Some(file) => self.add(package, &file, seen).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// what a module can import, by the paths it'd write for them: everything in its own package,
+    /// plus whatever its imports out of that package turned out to be
+    pub fn exports(&self, module: usize) -> Exports<'_> {
+        let this: &Module = &self.modules[module];
+        let mut modules: HashMap<&str, &[String]> = HashMap::new();
+        for other in self.modules.iter().filter(|m| m.package == this.package) {
+            modules.insert(&other.name, &other.exports);
+        }
+        for (import, found) in &this.imports {
+            if let Some(found) = found {
+                modules.insert(import, &self.modules[*found].exports);
+            }
+        }
+        Exports { modules }
+    }
+
+    /// everything checking a module depends on: its source, the interface of each of its imports (or that it
+    /// didn't resolve) and whatever else the caller passes in (the lint levels). same fingerprint, same result
+    pub fn fingerprint(&self, module: usize, extra: &str) -> u64 {
+        let this: &Module = &self.modules[module];
+        let mut hasher: Fnv = Fnv::default();
+        hasher.write(&this.hash.to_le_bytes());
+        for (import, found) in &this.imports {
+            hasher.str(import);
+            match found {
+                Some(idx) => hasher.write(&self.modules[*idx].interface.to_le_bytes()),
+                None => hasher.str("unresolved"),
+            }
+        }
+        hasher.str(extra);
+        hasher.finish()
+    }
+
+    /// remember that a module checked clean with this fingerprint
+    pub fn checked(&mut self, module: usize, fingerprint: u64) {
+        if let Some(entry) = self.cache.entries.get_mut(&self.modules[module].key) {
+            entry.checked = Some(fingerprint);
+        }
+    }
+
+    /// write the cache next to the root Stick.toml
+    pub fn save_cache(&self) -> io::Result<()> {
+        let keep: Vec<&str> = self.modules.iter().map(|m| m.key.as_str()).collect();
+        self.cache.save(&self.packages[0].dir, &keep)
+    }
+}
+
+// what a file imports and declares at the top level. broken files still give back whatever did parse,
+// the errors come out properly when the module gets checked
+fn scan(file: &SourceFile, source: u64) -> Entry {
+    let mut entry: Entry = Entry {
+        source,
+        ..Entry::default()
+    };
+    let Ok(lexed) = lex(file, &Flags::QUIET) else {
+        return entry;
+    };
+    let (ast, _): (Ast, Vec<Diagnostic>) = Parser::new(file, &lexed).parse(&Flags::QUIET);
+
+    let mut interface: Fnv = Fnv::default();
+    for stmt in &ast.stmts {
+        match &stmt.kind {
+            StmtKind::Import { module, .. } => {
+                let module: String = module.iter().map(|part| part.as_str()).collect::<Vec<_>>().join("::");
+                if !entry.imports.contains(&module) {
+                    entry.imports.push(module);
+                }
+            }
+
+            // what other modules see is the declaration without its value, except consts (their value
+            // is the point, and can end up inlined anywhere). docs and bodies can change all they like
+            StmtKind::VarDecl { name, constant, .. } => {
+                let full: String = ast.show(stmt).to_string();
+                let signature: &str = match constant {
+                    true => &full,
+                    false => full.split_once(" = ").map_or(full.as_str(), |(sig, _)| sig),
+                };
+                interface.str(signature);
+                entry.exports.push(name.to_string());
+            }
+            _ => {}
+        }
+    }
+    entry.interface = interface.finish();
+    entry
+}
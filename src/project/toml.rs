@@ -0,0 +1,321 @@
+//! just enough toml for Stick.toml: `[section]` headers, `key = value` lines and # comments, where a value is a
+//! "string", a whole number, true/false, an [array] or an { inline = "table" }. arrays and tables stay on one line.
+//! strings are taken as written (no escapes), they only ever hold names and paths
+use crate::{
+    error::{Diagnostic, ManifestError, Severity, SyntaxError},
+    source::SourceFile,
+};
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<'src> {
+    Str(&'src str),
+    Int(i64),
+    Bool(bool),
+    Array(Vec<(Value<'src>, Range<usize>)>),
+    Table(Vec<Entry<'src>>),
+}
+
+impl Value<'_> {
+    /// what kind of value this is, for messages
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::Str(_) => "a string",
+            Value::Int(_) => "a number",
+            Value::Bool(_) => "a bool",
+            Value::Array(_) => "an array",
+            Value::Table(_) => "a table",
+        }
+    }
+}
+
+/// one `key = value`, with where the key and the value are
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry<'src> {
+    pub key: &'src str,
+    pub key_span: Range<usize>,
+    pub value: Value<'src>,
+    pub value_span: Range<usize>,
+}
+
+/// everything under one `[header]`. keys before the first header land in a section named ""
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section<'src> {
+    pub name: &'src str,
+    pub span: Range<usize>,
+    pub entries: Vec<Entry<'src>>,
+}
+
+/// parse a whole file. bad lines get reported and skipped so one typo doesn't hide the rest
+pub fn parse(file: &SourceFile) -> (Vec<Section<'_>>, Vec<Diagnostic>) {
+    let src: &str = &file.src;
+    let mut sections: Vec<Section<'_>> = vec![Section {
+        name: "",
+        span: 0..0,
+        entries: Vec::new(),
+    }];
+    let mut errors: Vec<Diagnostic> = Vec::new();
+
+    let mut offset: usize = 0;
+    for line in src.split_inclusive('\n') {
+        let mut cursor: Cursor<'_> = Cursor { src, pos: offset, end: offset + line.trim_end().len() };
+        offset += line.len();
+
+        // an entry only gets kept if the whole line's good. a header gets kept anyway, so the keys under it
+        // still land in the right section
+        let result: Result<(), (Range<usize>, ManifestError)> = match cursor.skip_ws() {
+            None | Some(b'#') => Ok(()),
+            Some(b'[') => cursor.header().map(|section| sections.push(section)).and_then(|_| cursor.finish()),
+            Some(_) => cursor
+                .entry()
+                .and_then(|entry| cursor.finish().map(|_| sections.last_mut().unwrap().entries.push(entry))),
+        };
+
+        if let Err((span, err)) = result {
+            errors.push(Diagnostic::new(file.span(span), SyntaxError::Manifest(err), Severity::Error));
+        }
+    }
+
+    (sections, errors)
+}
+
+// walks one line (end stops before the newline)
+struct Cursor<'src> {
+    src: &'src str,
+    pos: usize,
+    end: usize,
+}
+
+type Parsed<T> = Result<T, (Range<usize>, ManifestError)>;
+
+impl<'src> Cursor<'src> {
+    fn peek(&self) -> Option<u8> {
+        (self.pos < self.end).then(|| self.src.as_bytes()[self.pos])
+    }
+
+    // skips spaces and hands back whatever's next
+    fn skip_ws(&mut self) -> Option<u8> {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\r')) {
+            self.pos += 1;
+        }
+        self.peek()
+    }
+
+    fn malformed(&self) -> (Range<usize>, ManifestError) {
+        (self.pos..self.end.max(self.pos + 1).min(self.src.len()), ManifestError::Malformed)
+    }
+
+    fn eat(&mut self, byte: u8) -> Parsed<()> {
+        match self.skip_ws() == Some(byte) {
+            true => {
+                self.pos += 1;
+                Ok(())
+            }
+            false => Err(self.malformed()),
+        }
+    }
+
+    // nothing but a comment can come after the thing on this line
+    fn finish(&mut self) -> Parsed<()> {
+        match self.skip_ws() {
+            None | Some(b'#') => Ok(()),
+            Some(_) => Err(self.malformed()),
+        }
+    }
+
+    // bare keys are letters, digits, - and _. dots are allowed too so `[profile.release]` is one name
+    fn key(&mut self, dots: bool) -> Parsed<(&'src str, Range<usize>)> {
+        self.skip_ws();
+        let start: usize = self.pos;
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_alphanumeric() || c == b'-' || c == b'_' || (dots && c == b'.')) {
+                break;
+            }
+            self.pos += 1;
+        }
+        match self.pos > start {
+            true => Ok((&self.src[start..self.pos], start..self.pos)),
+            false => Err(self.malformed()),
+        }
+    }
+
+    fn header(&mut self) -> Parsed<Section<'src>> {
+        let start: usize = self.pos;
+        self.eat(b'[')?;
+        let (name, _) = self.key(true)?;
+        self.eat(b']')?;
+        Ok(Section {
+            name,
+            span: start..self.pos,
+            entries: Vec::new(),
+        })
+    }
+
+    fn entry(&mut self) -> Parsed<Entry<'src>> {
+        let (key, key_span) = self.key(false)?;
+        self.eat(b'=')?;
+        let (value, value_span) = self.value()?;
+        Ok(Entry {
+            key,
+            key_span,
+            value,
+            value_span,
+        })
+    }
+
+    fn value(&mut self) -> Parsed<(Value<'src>, Range<usize>)> {
+        let start: usize = match self.skip_ws() {
+            Some(_) => self.pos,
+            None => return Err(self.malformed()),
+        };
+
+        let value: Value<'_> = match self.peek() {
+            Some(b'"') => {
+                self.pos += 1;
+                let len: usize = match self.src[self.pos..self.end].find('"') {
+                    Some(len) => len,
+                    None => return Err((start..self.end, ManifestError::InvalidValue(self.src[start..self.end].to_string()))),
+                };
+                self.pos += len + 1;
+                Value::Str(&self.src[start + 1..self.pos - 1])
+            }
+
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items: Vec<(Value<'_>, Range<usize>)> = Vec::new();
+
+                // trailing commas are fine
+                while self.skip_ws() != Some(b']') {
+                    items.push(self.value()?);
+                    if self.skip_ws() != Some(b',') {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                self.eat(b']')?;
+                Value::Array(items)
+            }
+
+            Some(b'{') => {
+                self.pos += 1;
+                let mut entries: Vec<Entry<'_>> = Vec::new();
+                while self.skip_ws() != Some(b'}') {
+                    entries.push(self.entry()?);
+                    if self.skip_ws() != Some(b',') {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                self.eat(b'}')?;
+                Value::Table(entries)
+            }
+
+            // anything else is a bare word, which has to be a number or a bool
+            _ => {
+                while let Some(c) = self.peek() {
+                    if matches!(c, b',' | b']' | b'}' | b'#' | b' ' | b'\t') {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                let word: &str = &self.src[start..self.pos];
+                match word {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    _ => match word.replace('_', "").parse::<i64>() {
+                        Ok(n) => Value::Int(n),
+                        Err(_) if word.is_empty() => return Err(self.malformed()),
+                        Err(_) => return Err((start..self.pos, ManifestError::InvalidValue(word.to_string()))),
+                    },
+                }
+            }
+        };
+
+        Ok((value, start..self.pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::SourceMap;
+
+    // parses `src` and hands back the sections and the error codes. the source gets leaked so what comes back
+    // can keep borrowing it
+    fn parsed(src: &str) -> (Vec<Section<'static>>, Vec<&'static str>) {
+        let sources: &'static mut SourceMap = Box::leak(Box::new(SourceMap::new()));
+        let id = sources.add("Stick.toml", src);
+        let (sections, errors) = parse(sources.get(id));
+        (sections, errors.iter().map(|d| d.err.code()).collect())
+    }
+
+    // an entry's key and value, without the spans
+    fn pairs<'s>(section: &Section<'s>) -> Vec<(&'s str, Value<'s>)> {
+        section.entries.iter().map(|e| (e.key, e.value.clone())).collect()
+    }
+
+    #[test]
+    fn sections_and_values() {
+        let (sections, errors) = parsed(
+            "# a comment\n[package]\nname = \"demo\"  # trailing\nsize = 1_000\nfast = true\n\n[profile.release]\nsrc = [\"a\", \"b\",]\ndep = { path = \"../x\" }\n",
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+        let names: Vec<&str> = sections.iter().map(|s| s.name).collect();
+        assert_eq!(names, ["", "package", "profile.release"]);
+        assert!(sections[0].entries.is_empty());
+
+        assert_eq!(
+            pairs(&sections[1]),
+            [("name", Value::Str("demo")), ("size", Value::Int(1000)), ("fast", Value::Bool(true))]
+        );
+        assert_eq!(sections[1].entries[0].key_span, 22..26);
+        assert_eq!(sections[1].entries[0].value_span, 29..35);
+
+        let Value::Array(items) = &sections[2].entries[0].value else {
+            panic!("not an array: {:?}", sections[2].entries[0]);
+        };
+        let items: Vec<&Value<'_>> = items.iter().map(|(v, _)| v).collect();
+        assert_eq!(items, [&Value::Str("a"), &Value::Str("b")]);
+
+        let Value::Table(fields) = &sections[2].entries[1].value else {
+            panic!("not a table: {:?}", sections[2].entries[1]);
+        };
+        assert_eq!((fields[0].key, &fields[0].value), ("path", &Value::Str("../x")));
+    }
+
+    #[test]
+    fn strings_are_taken_as_written() {
+        // no escapes, and a # inside quotes isn't a comment
+        let (sections, errors) = parsed("[a]\npath = \"C:\\dir\\n\"\ntag = \"#1\" # real comment\n");
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(pairs(&sections[1]), [("path", Value::Str("C:\\dir\\n")), ("tag", Value::Str("#1"))]);
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let (sections, errors) = parsed("[package]\r\nname = \"demo\"\r\n");
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(pairs(&sections[1]), [("name", Value::Str("demo"))]);
+    }
+
+    #[test]
+    fn bad_lines_are_reported_and_skipped() {
+        let (sections, errors) = parsed("[package\nname = \"open\nversion = one\njust words\nkey = 1 2\nok = 1\n");
+        assert_eq!(errors, ["E0200", "E0201", "E0201", "E0200", "E0200"]);
+
+        // the good line still made it in
+        assert_eq!(pairs(&sections[0]), [("ok", Value::Int(1))]);
+
+        // junk after a header is an error, but what's under it is still in that section
+        let (sections, errors) = parsed("[package] name\nname = \"demo\"\n");
+        assert_eq!(errors, ["E0200"]);
+        assert_eq!(sections[1].name, "package");
+        assert_eq!(pairs(&sections[1]), [("name", Value::Str("demo"))]);
+    }
+
+    #[test]
+    fn missing_value() {
+        let (_, errors) = parsed("name =\nlist = [1,\n");
+        assert_eq!(errors, ["E0200", "E0200"]);
+    }
+}
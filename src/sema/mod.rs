@@ -0,0 +1,3 @@
+pub mod resolve;
+
+pub use resolve::{Exports, SymbolIndex, index, resolve, resolve_module};
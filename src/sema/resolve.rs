@@ -0,0 +1,640 @@
+use std::collections::HashMap;
+
+use crate::error::{
+    Diagnostic, SemaError, Severity, SyntaxError, Warning,
+    suggest::{BUILTIN_TYPES, KEYWORDS, did_you_mean},
+};
+use crate::parser::ast::*;
+use crate::source::SourceFile;
+
+// one declared name, and whether anything ever read it
+struct Binding {
+    name: Ident,
+    span: Span,
+    decl: Span,
+    typ: Option<Span>,
+    depth: usize,
+    used: bool,
+
+    // what the declaration allows (fixed = const or static)
+    mutable: bool,
+    fixed: bool,
+    initialized: bool,
+}
+
+// the parts of a declaration the resolver cares about
+#[derive(Clone, Copy, Default)]
+struct DeclInfo {
+    // the whole declaring statement and the type annotation (if written), for tooling like hover
+    decl: Span,
+    typ: Option<Span>,
+    mutable: bool,
+    fixed: bool,
+    initialized: bool,
+}
+
+// names visible in one block. start is the first binding declared inside it. names are interned, so lookups
+// hash and compare a u32 instead of a string
+struct Scope {
+    start: usize,
+    names: HashMap<Ident, usize>,
+}
+
+/// what every module a file can import exports, keyed by the path as it'd be written in that file (`a::b`).
+/// built by the project loader (borrowing from it), a lone file doesn't have one
+#[derive(Debug, Default, Clone)]
+pub struct Exports<'e> {
+    pub modules: HashMap<&'e str, &'e [String]>,
+}
+
+/// walks the tree keeping track of what's in scope. reports duplicate fixed declarations and
+/// assignments to immutable names, plus warnings (unused variables, shadowing, unreachable code).
+/// error nodes are skipped, so this runs fine on files that didn't parse cleanly
+pub struct Resolver<'src, 't, 'e> {
+    src: &'src str,
+    ast: &'t Ast,
+    bindings: Vec<Binding>,
+    scopes: Vec<Scope>,
+    diagnostics: Vec<Diagnostic>,
+
+    // every resolved read or write and the binding it hit
+    uses: Vec<(Span, usize)>,
+
+    // None when checking a file on its own, then imports just bind their names without being checked
+    exports: Option<&'e Exports<'e>>,
+}
+
+/// a declared name, as the lsp and other tooling see it (not an interned `Symbol`, that's just the name)
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    pub name: Ident,
+    pub span: Span,
+    pub decl: Span,
+    pub typ: Option<Span>,
+    pub mutable: bool,
+    pub fixed: bool,
+}
+
+/// every declaration in a file plus every place one gets used (uses point into declarations)
+#[derive(Debug, Default)]
+pub struct SymbolIndex {
+    pub declarations: Vec<Declaration>,
+    pub uses: Vec<(Span, usize)>,
+}
+
+impl SymbolIndex {
+    /// the declaration made or used at a byte offset (the end of a name counts, that's where cursors sit)
+    pub fn at(&self, offset: usize) -> Option<usize> {
+        let hit = |span: &Span| span.start <= offset && offset <= span.end;
+        self.declarations
+            .iter()
+            .position(|s| hit(&s.span))
+            .or_else(|| self.uses.iter().find(|(span, _)| hit(span)).map(|(_, idx)| *idx))
+    }
+
+    /// every use of a declaration, in source order
+    pub fn references(&self, decl: usize) -> Vec<Span> {
+        self.uses.iter().filter(|(_, idx)| *idx == decl).map(|(span, _)| *span).collect()
+    }
+}
+
+/// resolve a whole file, handing back every diagnostic in source order
+pub fn resolve(file: &SourceFile, ast: &Ast) -> Vec<Diagnostic> {
+    let mut resolver: Resolver<'_, '_, '_> = Resolver::run(file, ast, None);
+    resolver.diagnostics.sort_by_key(|d| d.span.start);
+    resolver.diagnostics
+}
+
+/// resolve one module of a project, where imports get checked against what the other modules export
+pub fn resolve_module(
+    file: &SourceFile,
+    ast: &Ast,
+    exports: &Exports<'_>,
+) -> Vec<Diagnostic> {
+    let mut resolver: Resolver<'_, '_, '_> = Resolver::run(file, ast, Some(exports));
+    resolver.diagnostics.sort_by_key(|d| d.span.start);
+    resolver.diagnostics
+}
+
+/// same walk as resolve, but keeps what it found instead of what went wrong
+pub fn index(file: &SourceFile, ast: &Ast) -> SymbolIndex {
+    let resolver: Resolver<'_, '_, '_> = Resolver::run(file, ast, None);
+    let declarations: Vec<Declaration> = resolver
+        .bindings
+        .iter()
+        .map(|b| Declaration {
+            name: b.name,
+            span: b.span,
+            decl: b.decl,
+            typ: b.typ,
+            mutable: b.mutable,
+            fixed: b.fixed,
+        })
+        .collect();
+    SymbolIndex {
+        declarations,
+        uses: resolver.uses,
+    }
+}
+
+impl<'src, 't, 'e> Resolver<'src, 't, 'e> {
+    fn run(file: &'src SourceFile, ast: &'t Ast, exports: Option<&'e Exports<'e>>) -> Self {
+        let mut resolver: Resolver<'_, '_, '_> = Resolver {
+            src: &file.src,
+            ast,
+            bindings: Vec::new(),
+            scopes: Vec::new(),
+            diagnostics: Vec::new(),
+            uses: Vec::new(),
+            exports,
+        };
+        resolver.block(&ast.stmts);
+        resolver
+    }
+
+    fn diagnostic(&self, span: Span, err: SyntaxError, severity: Severity) -> Diagnostic {
+        Diagnostic::new(span, err, severity)
+    }
+
+    fn warn(&mut self, span: Span, warning: Warning) {
+        let diag: Diagnostic = self.diagnostic(span, SyntaxError::Warning(warning), Severity::Warning);
+        self.diagnostics.push(diag);
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(Scope {
+            start: self.bindings.len(),
+            names: HashMap::new(),
+        });
+    }
+
+    // report anything in the scope nobody read. _names are opted out
+    fn pop_scope(&mut self) {
+        let depth: usize = self.scopes.len();
+        let Some(scope) = self.scopes.pop() else {
+            return;
+        };
+
+        // in a project the top level is what other modules import, so nothing there is unused
+        if depth == 1 && self.exports.is_some() {
+            return;
+        }
+
+        for i in scope.start..self.bindings.len() {
+            let binding: &Binding = &self.bindings[i];
+            if binding.depth == depth && !binding.used && !binding.name.as_str().starts_with('_') {
+                let (span, name) = (binding.span, binding.name);
+                self.warn(span, Warning::UnusedVariable(name));
+            }
+        }
+    }
+
+    fn lookup(&self, name: Ident) -> Option<usize> {
+        self.scopes.iter().rev().find_map(|scope| scope.names.get(&name).copied())
+    }
+
+    fn declare(&mut self, name: Ident, span: Span, info: DeclInfo) {
+        if let Some(prev) = self.lookup(name) {
+            let (prev_span, prev_fixed) = (self.bindings[prev].span, self.bindings[prev].fixed);
+            let same_scope: bool = self.bindings[prev].depth == self.scopes.len();
+
+            // consts and statics are fixed, so they can't be redeclared in the same scope at all
+            let diag: Diagnostic = if same_scope && (prev_fixed || info.fixed) {
+                self.diagnostic(
+                    span,
+                    SyntaxError::Sema(SemaError::DuplicateDeclaration(name)),
+                    Severity::Error,
+                )
+                .with_primary("declared again here")
+                .with_label(prev_span, "first declared here")
+            } else {
+                self.diagnostic(span, SyntaxError::Warning(Warning::Shadowing(name)), Severity::Warning)
+                    .with_primary("new declaration here")
+                    .with_label(prev_span, "previously declared here")
+                    .with_note(format!("any use of '{name}' after this refers to the new declaration"))
+            };
+            self.diagnostics.push(diag);
+        }
+
+        let depth: usize = self.scopes.len();
+        self.bindings.push(Binding {
+            name,
+            span,
+            decl: info.decl,
+            typ: info.typ,
+            depth,
+            used: false,
+            mutable: info.mutable,
+            fixed: info.fixed,
+            initialized: info.initialized,
+        });
+
+        let idx: usize = self.bindings.len() - 1;
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.names.insert(name, idx);
+        }
+    }
+
+    // reading a name marks it used
+    fn read(&mut self, name: Ident, span: Span) {
+        match self.lookup(name) {
+            Some(idx) => {
+                self.bindings[idx].used = true;
+                self.uses.push((span, idx));
+            }
+            None => self.unresolved(name, span),
+        }
+    }
+
+    // everything visible from here, most recently declared first (so ties in suggestions go to the closest one)
+    fn visible(&self) -> Vec<&'src str> {
+        let mut idxs: Vec<usize> = self.scopes.iter().flat_map(|scope| scope.names.values().copied()).collect();
+        idxs.sort_unstable_by(|a, b| b.cmp(a));
+        idxs.into_iter().map(|idx| self.bindings[idx].name.as_str()).collect()
+    }
+
+    // keywords are candidates too, a misspelled one just lexes as a name (retrun, whiel)
+    fn unresolved(&mut self, name: Ident, span: Span) {
+        let visible: Vec<&str> = self.visible();
+        let suggestion: Option<&str> = did_you_mean(name.as_str(), visible.into_iter().chain(KEYWORDS.iter().copied()));
+
+        let mut diag: Diagnostic = self
+            .diagnostic(span, SyntaxError::Sema(SemaError::UnresolvedName(name)), Severity::Error)
+            .with_primary("not found in this scope");
+        if let Some(s) = suggestion {
+            diag = diag.with_suggestion(span, s, format!("did you mean `{s}`?"));
+        }
+        self.diagnostics.push(diag);
+    }
+
+    // user types don't exist yet, so any name that isn't a builtin is unknown. paths are left for imports
+    fn typ(&mut self, typ: &Type) {
+        match &typ.kind {
+            TypeKind::Ident(name) => {
+                let mut diag: Diagnostic = self
+                    .diagnostic(typ.span, SyntaxError::Sema(SemaError::UnknownType(*name)), Severity::Error)
+                    .with_primary("not a type");
+                if let Some(s) = did_you_mean(name.as_str(), BUILTIN_TYPES.iter().copied()) {
+                    diag = diag.with_suggestion(typ.span, s, format!("did you mean `{s}`?"));
+                }
+                self.diagnostics.push(diag);
+            }
+            TypeKind::Tuple(types) => {
+                for t in types {
+                    self.typ(t);
+                }
+            }
+            TypeKind::Array { typ, .. } => self.typ(typ),
+            TypeKind::Func { params, ret } => {
+                for t in params {
+                    self.typ(t);
+                }
+                self.typ(ret);
+            }
+            _ => {}
+        }
+    }
+
+    // plain assignment. the first one to something declared without a value is its initialization
+    fn write(&mut self, name: Ident, span: Span) {
+        let Some(idx) = self.lookup(name) else {
+            self.unresolved(name, span);
+            return;
+        };
+        self.uses.push((span, idx));
+
+        let binding: &mut Binding = &mut self.bindings[idx];
+        if binding.mutable || (!binding.initialized && !binding.fixed) {
+            binding.initialized = true;
+            return;
+        }
+
+        let (decl, fixed): (Span, bool) = (binding.span, binding.fixed);
+        let mut diag: Diagnostic = self
+            .diagnostic(span, SyntaxError::Sema(SemaError::AssignToImmutable(name)), Severity::Error)
+            .with_primary("assigned here")
+            .with_label(decl, "declared immutable here")
+            .with_help(format!("try `let mutable {name}` instead"));
+
+        // only a plain `let` can just be made mutable (loop and pattern bindings have nowhere to put it).
+        // never applied automatically since it changes what the declaration means
+        if !fixed && self.src[..decl.start].trim_end().ends_with("let") {
+            diag = diag.with_suggestion(Span::new(decl.file, decl.start, decl.start), "mutable ", "add `mutable` here");
+        }
+        self.diagnostics.push(diag);
+    }
+
+    fn block(&mut self, stmts: &'t [Stmt]) {
+        self.push_scope();
+
+        // only the first unreachable statement gets flagged, spanning the rest of the block. the parser doesn't
+        // make return/break/continue yet, so this never happens until it does
+        let mut diverged: bool = false;
+        for (i, stmt) in stmts.iter().enumerate() {
+            if diverged {
+                let span: Span = stmt.span.to(stmts[stmts.len() - 1].span);
+                self.warn(span, Warning::UnreachableCode);
+                self.stmts(&stmts[i..]);
+                break;
+            }
+
+            self.stmt(stmt);
+            diverged = matches!(
+                stmt.kind,
+                StmtKind::Return(_) | StmtKind::Break | StmtKind::Continue
+            );
+        }
+
+        self.pop_scope();
+    }
+
+    // same as block but without the new scope or reachability checks
+    fn stmts(&mut self, stmts: &'t [Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &'t Stmt) {
+        match &stmt.kind {
+            StmtKind::Expr(expr) => self.expr(*expr),
+            StmtKind::Return(expr) => {
+                if let Some(expr) = expr {
+                    self.expr(*expr);
+                }
+            }
+
+            // the right hand side can't see the name it's declaring
+            StmtKind::VarDecl {
+                name,
+                name_span,
+                typ,
+                init,
+                mutable,
+                constant,
+                global,
+                ..
+            } => {
+                self.typ(typ);
+                if let Some(init) = init {
+                    self.expr(*init);
+                }
+
+                let info: DeclInfo = DeclInfo {
+                    decl: stmt.span,
+                    typ: (typ.kind != TypeKind::Inferred).then_some(typ.span),
+                    mutable: *mutable,
+                    fixed: *constant || *global,
+                    initialized: init.is_some(),
+                };
+                self.declare(*name, *name_span, info);
+            }
+
+            StmtKind::Import {
+                module_span, names, ..
+            } => self.import(stmt.span, *module_span, names),
+
+            StmtKind::Break | StmtKind::Continue | StmtKind::Error => {}
+        }
+    }
+
+    // imported names act like consts declared by the import. they count as used from the start,
+    // an unused variable warning would be the wrong thing to say about them
+    fn import(&mut self, decl: Span, module_span: Span, names: &[(Ident, Span)]) {
+        let module: &'src str = &self.src[module_span.range()];
+        let exported: Option<&'e [String]> = match self.exports {
+            Some(exports) => match exports.modules.get(module) {
+                Some(exported) => Some(*exported),
+                None => {
+                    let mut diag: Diagnostic = self
+                        .diagnostic(module_span, SyntaxError::Sema(SemaError::UnresolvedModule(module.to_string())), Severity::Error)
+                        .with_primary("no module by this name");
+                    if let Some(s) = did_you_mean(module, exports.modules.keys().copied()) {
+                        diag = diag.with_suggestion(module_span, s, format!("did you mean `{s}`?"));
+                    }
+                    self.diagnostics.push(diag);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        for (name, span) in names {
+            if let Some(exported) = exported
+                && !exported.iter().any(|e| e == name.as_str())
+            {
+                let mut diag: Diagnostic = self
+                    .diagnostic(*span, SyntaxError::Sema(SemaError::UnresolvedImport(*name)), Severity::Error)
+                    .with_primary(format!("not in `{module}`"));
+                if let Some(s) = did_you_mean(name.as_str(), exported.iter().map(String::as_str)) {
+                    diag = diag.with_suggestion(*span, s, format!("did you mean `{s}`?"));
+                }
+                self.diagnostics.push(diag);
+            }
+
+            let info: DeclInfo = DeclInfo {
+                decl,
+                typ: None,
+                mutable: false,
+                fixed: true,
+                initialized: true,
+            };
+            self.declare(*name, *span, info);
+            let idx: usize = self.bindings.len() - 1;
+            self.bindings[idx].used = true;
+        }
+    }
+
+    fn expr(&mut self, id: ExprId) {
+        let expr: &'t Expr = &self.ast[id];
+        match &expr.kind {
+            ExprKind::Ident(name) => self.read(*name, expr.span),
+            ExprKind::Literal(_) | ExprKind::Error => {}
+
+            // plain assignment is a write not a read, fields and subscripts still read the object
+            ExprKind::Assign { lhs, rhs, .. } => {
+                self.expr(*rhs);
+                match lhs {
+                    LeftSide::Var(name, span) => self.write(*name, *span),
+                    LeftSide::Field { obj, .. } => self.expr(*obj),
+                    LeftSide::Subscript { obj, sub } => {
+                        self.expr(*obj);
+                        self.subscript(sub);
+                    }
+                }
+            }
+
+            ExprKind::Unary { expr, .. } => self.expr(*expr),
+            ExprKind::Binary { lhs, rhs, .. } => {
+                self.expr(*lhs);
+                self.expr(*rhs);
+            }
+
+            ExprKind::Call { func, args } => {
+                self.expr(*func);
+                for arg in args {
+                    self.expr(*arg);
+                }
+            }
+            ExprKind::Field { obj, .. } => self.expr(*obj),
+            ExprKind::Method { receiver, args, .. } => {
+                self.expr(*receiver);
+                for arg in args {
+                    self.expr(*arg);
+                }
+            }
+            ExprKind::Index { obj, sub } => {
+                self.expr(*obj);
+                self.subscript(sub);
+            }
+
+            // control flow opens new scopes for its bodies
+            ExprKind::If { cond, then, else_ } => {
+                self.expr(*cond);
+                self.block(then);
+                if let Some(else_) = else_ {
+                    self.block(else_);
+                }
+            }
+            ExprKind::While { cond, body } => {
+                self.expr(*cond);
+                self.block(std::slice::from_ref(body));
+            }
+            ExprKind::For { name, iter, body } => {
+                self.expr(*iter);
+                self.push_scope();
+                self.declare(*name, expr.span, DeclInfo { decl: expr.span, ..DeclInfo::default() });
+                self.block(std::slice::from_ref(body));
+                self.pop_scope();
+            }
+            ExprKind::Match { item, branches } => {
+                self.expr(*item);
+                for branch in branches {
+                    self.push_scope();
+                    self.pattern(&branch.pattern);
+                    if let Some(guard) = &branch.guard {
+                        self.expr(*guard);
+                    }
+                    self.block(std::slice::from_ref(&branch.body));
+                    self.pop_scope();
+                }
+            }
+        }
+    }
+
+    fn subscript(&mut self, sub: &'t Subscript) {
+        match sub {
+            Subscript::Index(idx) => self.expr(*idx),
+            Subscript::Range { start, end } => {
+                for bound in [start, end].into_iter().flatten() {
+                    self.expr(*bound);
+                }
+            }
+        }
+    }
+
+    fn pattern(&mut self, pattern: &'t Pattern) {
+        match &pattern.kind {
+            PatternKind::Ident(name) => self.declare(*name, pattern.span, DeclInfo { decl: pattern.span, ..DeclInfo::default() }),
+            PatternKind::Or(patterns) => {
+                for p in patterns {
+                    self.pattern(p);
+                }
+            }
+            PatternKind::Range { start, end } => {
+                for bound in [start, end].into_iter().flatten() {
+                    self.expr(*bound);
+                }
+            }
+            PatternKind::Wildcard | PatternKind::Literal(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cli::Flags, lexer::lex, parser::Parser, source::SourceMap};
+
+    // parse and resolve, the same diagnostics `stick check` would print for a lone file
+    fn check_with(src: &str, edit: impl FnOnce(&mut Ast)) -> Vec<Diagnostic> {
+        let mut sources: SourceMap = SourceMap::new();
+        let file = sources.add("test.stick", src);
+        let lexed = lex(sources.get(file), &Flags::QUIET).unwrap_or_else(|_| panic!("didn't lex"));
+        let (mut ast, mut diagnostics) = Parser::new(sources.get(file), &lexed).parse(&Flags::QUIET);
+        edit(&mut ast);
+        diagnostics.extend(resolve(sources.get(file), &ast));
+        diagnostics
+    }
+
+    fn codes(src: &str) -> Vec<&'static str> {
+        check_with(src, |_| {}).iter().map(|d| d.err.code()).collect()
+    }
+
+    const CLEAN: &str = "let x = 1\nlet mutable y = x * 2\ny = y + 1\nlet _z = y\n";
+
+    #[test]
+    fn clean_code_is_quiet() {
+        assert_eq!(codes(CLEAN), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn unnecessary_semicolon() {
+        assert_eq!(codes("let _x = 1;\n"), ["W0001"]);
+        assert_eq!(codes("let _x = 1; let _y = 2\n"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn unused_variable() {
+        assert_eq!(codes("let x = 1\n"), ["W0002"]);
+        assert_eq!(codes("let x = 1\nlet _y = x\n"), Vec::<&str>::new());
+
+        // writing to it isn't using it
+        assert_eq!(codes("let mutable x = 1\nx = 2\n"), ["W0002"]);
+    }
+
+    #[test]
+    fn shadowing() {
+        assert_eq!(codes("let x = 1\nlet x = x + 1\nlet _y = x\n"), ["W0004"]);
+        assert_eq!(codes("let x = 1\nlet y = x + 1\nlet _z = y\n"), Vec::<&str>::new());
+    }
+
+    // the parser doesn't make return yet, so swap one into an otherwise clean tree by hand
+    #[test]
+    fn unreachable_code() {
+        let returned = check_with(CLEAN, |ast| ast.stmts[1].kind = StmtKind::Return(None));
+        let found: Vec<&str> = returned.iter().map(|d| d.err.code()).collect();
+        assert!(found.contains(&"W0003"), "{found:?}");
+
+        // spans from the first statement that can't run to the end of the block
+        let unreachable: &Diagnostic = returned.iter().find(|d| d.err.code() == "W0003").unwrap();
+        assert_eq!(unreachable.span.range(), CLEAN.find("y = y").unwrap()..CLEAN.trim_end().len());
+
+        assert!(!codes(CLEAN).contains(&"W0003"));
+    }
+
+    // the fix replaces the misspelled name with the closest declared one, the nearest declaration winning a tie
+    #[test]
+    fn undefined_name_suggests_the_closest() {
+        let suggested = |src: &str| -> Vec<(String, std::ops::Range<usize>)> {
+            check_with(src, |_| {})
+                .iter()
+                .filter(|d| d.err.code() == "E0102")
+                .flat_map(|d| d.fixes.iter().map(|f| (f.replacement.clone(), f.span.range())))
+                .collect()
+        };
+
+        let src: &str = "let count = 1\nlet amount = 2\nlet _x = cuont + amount\n";
+        let typo: usize = src.find("cuont").unwrap();
+        assert_eq!(suggested(src), [("count".to_string(), typo..typo + 5)]);
+
+        let src: &str = "let total = 1\nlet _x = totl\n";
+        assert_eq!(suggested(src)[0].0, "total");
+        let src: &str = "let abcd = 1\nlet abce = 2\nlet _x = abcf + abcd + abce\n";
+        assert_eq!(suggested(src)[0].0, "abce");
+
+        // nothing close enough, no guess
+        let src: &str = "let count = 1\nlet _x = zzz + count\n";
+        assert_eq!(suggested(src), []);
+    }
+}
@@ -0,0 +1,371 @@
+//! every file the compiler has loaded, and where things are in them. a SourceMap owns the text of each file and
+//! hands out FileIds, so spans and diagnostics can say which file they're in without borrowing it.
+//!
+//! everything in the compiler works in byte offsets, but people (and editors, and code scanning tools) want
+//! line:column, and they don't agree on what a column is. a LineIndex gets built once per file (the first time
+//! anything asks) and answers in whichever encoding is asked for
+use std::{ops::Range, sync::OnceLock};
+
+/// which file in a SourceMap something is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct FileId(u32);
+
+/// a byte range in one file. Range<usize> isn't Copy, and these get copied around a LOT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub file: FileId,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    #[inline]
+    pub fn new(file: FileId, start: usize, end: usize) -> Self {
+        Span { file, start, end }
+    }
+
+    /// a range in a file, straight from the lexer
+    #[inline]
+    pub fn of(file: FileId, range: Range<usize>) -> Self {
+        Span::new(file, range.start, range.end)
+    }
+
+    /// merge two spans (in the same file), from whichever starts first to whichever ends last
+    #[inline]
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.file, self.start.min(other.start), self.end.max(other.end))
+    }
+
+    /// back to a plain range for slicing
+    #[inline]
+    pub fn range(self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// one loaded file
+#[derive(Debug)]
+pub struct SourceFile {
+    pub id: FileId,
+    pub path: String,
+    pub src: String,
+    lines: OnceLock<LineIndex>,
+}
+
+impl SourceFile {
+    /// where its lines start, worked out the first time it's needed (most files never have anything to report)
+    pub fn lines(&self) -> &LineIndex {
+        self.lines.get_or_init(|| LineIndex::new(&self.src))
+    }
+
+    /// a span covering a range of this file
+    #[inline]
+    pub fn span(&self, range: Range<usize>) -> Span {
+        Span::of(self.id, range)
+    }
+}
+
+/// every file loaded so far. files only ever get added (or have their text swapped out), so a FileId is good
+/// for as long as the map is around
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap::default()
+    }
+
+    pub fn add(&mut self, path: impl Into<String>, src: impl Into<String>) -> FileId {
+        let id: FileId = FileId(self.files.len() as u32);
+        self.files.push(SourceFile {
+            id,
+            path: path.into(),
+            src: src.into(),
+            lines: OnceLock::new(),
+        });
+        id
+    }
+
+    /// give a file new text (an editor changed it, or the repl got another line)
+    pub fn set(&mut self, id: FileId, src: impl Into<String>) {
+        let file: &mut SourceFile = &mut self.files[id.0 as usize];
+        file.src = src.into();
+        file.lines = OnceLock::new();
+    }
+
+    pub fn get(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0 as usize]
+    }
+
+    pub fn path(&self, id: FileId) -> &str {
+        &self.get(id).path
+    }
+
+    pub fn src(&self, id: FileId) -> &str {
+        &self.get(id).src
+    }
+}
+
+/// what a column counts. utf-8 is bytes, utf-16 is what the lsp (and sarif) want, utf-32 is chars
+#[allow(dead_code)] // nothing asks for byte columns yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16,
+    Utf32,
+}
+
+impl Encoding {
+    // how many columns a char that's `bytes` long takes up
+    fn width(self, bytes: usize) -> usize {
+        match self {
+            Encoding::Utf8 => bytes,
+            Encoding::Utf16 if bytes == 4 => 2,
+            Encoding::Utf16 | Encoding::Utf32 => 1,
+        }
+    }
+}
+
+/// a 1 based line and column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// where every line starts and where every non ascii char is. with those two, any offset converts to any kind of
+/// column without looking at the text again (ascii is one column in every encoding, so only the rest need counting)
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    len: usize,
+
+    // byte offset of the start of every line, starts[0] is always 0
+    starts: Vec<usize>,
+
+    // (offset, length in bytes) of every char that isn't ascii, in order
+    wide: Vec<(usize, usize)>,
+
+    // offset of every \r that's half of a \r\n, so a line's end can stop before it
+    crlf: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(src: &str) -> LineIndex {
+        let mut starts: Vec<usize> = vec![0];
+        let mut wide: Vec<(usize, usize)> = Vec::new();
+        let mut crlf: Vec<usize> = Vec::new();
+        for (i, c) in src.char_indices() {
+            match c {
+                '\n' => {
+                    if i > 0 && src.as_bytes()[i - 1] == b'\r' {
+                        crlf.push(i - 1);
+                    }
+                    starts.push(i + 1);
+                }
+                c if !c.is_ascii() => wide.push((i, c.len_utf8())),
+                _ => {}
+            }
+        }
+        LineIndex {
+            len: src.len(),
+            starts,
+            wide,
+            crlf,
+        }
+    }
+
+    /// how many lines there are, counted the way `str::lines` does (a trailing newline doesn't start a new one)
+    pub fn line_count(&self) -> usize {
+        match self.starts.last() {
+            Some(&last) if last == self.len => self.starts.len() - 1,
+            _ => self.starts.len(),
+        }
+    }
+
+    /// the bytes a 1 based line covers, not counting its newline (\n or \r\n). None past the last line
+    pub fn line(&self, line: usize) -> Option<Range<usize>> {
+        let start: usize = *self.starts.get(line.checked_sub(1)?)?;
+        let end: usize = match self.starts.get(line) {
+            Some(&next) if next.checked_sub(2).is_some_and(|i| self.crlf.binary_search(&i).is_ok()) => next - 2,
+            Some(&next) => next - 1,
+            None => self.len,
+        };
+        Some(start..end)
+    }
+
+    // the non ascii chars in a range of bytes
+    fn wide_in(&self, range: Range<usize>) -> &[(usize, usize)] {
+        let from: usize = self.wide.partition_point(|&(at, _)| at < range.start);
+        let to: usize = self.wide.partition_point(|&(at, _)| at < range.end);
+        &self.wide[from..to]
+    }
+
+    /// the line and column of a byte offset. anything past the end is the end, and an offset in the middle of a
+    /// char counts as the start of it
+    pub fn line_col(&self, offset: usize, encoding: Encoding) -> LineCol {
+        let offset: usize = offset.min(self.len);
+        let line: usize = self.starts.partition_point(|&start| start <= offset);
+        let start: usize = self.starts[line - 1];
+
+        let mut col: usize = offset - start;
+        for &(at, len) in self.wide_in(start..offset) {
+            // a char we're partway into only counts up to its start
+            let bytes: usize = len.min(offset - at);
+            col -= bytes;
+            if bytes == len {
+                col += encoding.width(len);
+            }
+        }
+        LineCol { line, col: col + 1 }
+    }
+
+    /// the byte offset of a line and column. a line past the end is the end of the file, and a column past the
+    /// end of its line is the end of the line
+    pub fn offset(&self, pos: LineCol, encoding: Encoding) -> usize {
+        let Some(line) = self.line(pos.line) else {
+            return self.len;
+        };
+
+        // walk along the line a wide char at a time, everything between them is one column a byte
+        let mut at: usize = line.start;
+        let mut left: usize = pos.col.saturating_sub(1);
+        for &(wide, len) in self.wide_in(line.clone()) {
+            if left <= wide - at {
+                return at + left;
+            }
+            left -= wide - at;
+            let width: usize = encoding.width(len);
+            if left < width {
+                return wide;
+            }
+            left -= width;
+            at = wide + len;
+        }
+        (at + left).min(line.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Encoding; 3] = [Encoding::Utf8, Encoding::Utf16, Encoding::Utf32];
+
+    fn at(line: usize, col: usize) -> LineCol {
+        LineCol { line, col }
+    }
+
+    #[test]
+    fn ascii_is_the_same_in_every_encoding() {
+        let index: LineIndex = LineIndex::new("let x = 1\nx\n");
+        for encoding in ALL {
+            assert_eq!(index.line_col(0, encoding), at(1, 1));
+            assert_eq!(index.line_col(4, encoding), at(1, 5));
+            assert_eq!(index.line_col(9, encoding), at(1, 10));
+            assert_eq!(index.line_col(10, encoding), at(2, 1));
+            assert_eq!(index.line_col(12, encoding), at(3, 1));
+        }
+    }
+
+    #[test]
+    fn multi_byte_chars() {
+        // é is 2 bytes and 1 utf-16 unit, 字 is 3 bytes and 1 unit
+        let src: &str = "é字x";
+        let index: LineIndex = LineIndex::new(src);
+        let x: usize = src.find('x').unwrap();
+        assert_eq!(index.line_col(x, Encoding::Utf8), at(1, 6));
+        assert_eq!(index.line_col(x, Encoding::Utf16), at(1, 3));
+        assert_eq!(index.line_col(x, Encoding::Utf32), at(1, 3));
+    }
+
+    #[test]
+    fn astral_chars_are_two_utf16_units() {
+        let src: &str = "a😀b";
+        let index: LineIndex = LineIndex::new(src);
+        let b: usize = src.find('b').unwrap();
+        assert_eq!(index.line_col(b, Encoding::Utf8), at(1, 6));
+        assert_eq!(index.line_col(b, Encoding::Utf16), at(1, 4));
+        assert_eq!(index.line_col(b, Encoding::Utf32), at(1, 3));
+
+        // a column in the middle of the surrogate pair lands on the start of the char
+        assert_eq!(index.offset(at(1, 3), Encoding::Utf16), 1);
+    }
+
+    #[test]
+    fn offset_inside_a_char_counts_as_its_start() {
+        let index: LineIndex = LineIndex::new("é!");
+        for encoding in ALL {
+            assert_eq!(index.line_col(1, encoding), index.line_col(0, encoding));
+        }
+    }
+
+    #[test]
+    fn crlf() {
+        let src: &str = "ab\r\ncd\r\n";
+        let index: LineIndex = LineIndex::new(src);
+        assert_eq!(index.line_count(), 2);
+        assert_eq!(index.line(1), Some(0..2));
+        assert_eq!(index.line(2), Some(4..6));
+        assert_eq!(index.line_col(4, Encoding::Utf16), at(2, 1));
+
+        // a column past the end of the line stops before the \r, not between it and the \n
+        assert_eq!(index.offset(at(1, 50), Encoding::Utf16), 2);
+    }
+
+    #[test]
+    fn leading_newline() {
+        // the first line is empty and the second starts at 1, so there's no room for a \r before its \n
+        let index: LineIndex = LineIndex::new("\nabc");
+        assert_eq!(index.line(1), Some(0..0));
+        assert_eq!(index.line(2), Some(1..4));
+        assert_eq!(index.offset(at(1, 5), Encoding::Utf16), 0);
+        assert_eq!(index.offset(at(2, 2), Encoding::Utf16), 2);
+
+        let index: LineIndex = LineIndex::new("\r\nabc");
+        assert_eq!(index.line(1), Some(0..0));
+        assert_eq!(index.line(2), Some(2..5));
+        assert_eq!(index.offset(at(1, 5), Encoding::Utf16), 0);
+        assert_eq!(index.line_col(2, Encoding::Utf16), at(2, 1));
+    }
+
+    #[test]
+    fn last_line_without_a_newline() {
+        let src: &str = "one\ntwo";
+        let index: LineIndex = LineIndex::new(src);
+        assert_eq!(index.line_count(), 2);
+        assert_eq!(index.line(2), Some(4..7));
+        assert_eq!(index.line(3), None);
+        assert_eq!(index.line_col(src.len(), Encoding::Utf8), at(2, 4));
+
+        // past the end is the end
+        assert_eq!(index.line_col(100, Encoding::Utf8), at(2, 4));
+        assert_eq!(index.offset(at(2, 100), Encoding::Utf8), src.len());
+        assert_eq!(index.offset(at(9, 1), Encoding::Utf8), src.len());
+    }
+
+    #[test]
+    fn empty_file() {
+        let index: LineIndex = LineIndex::new("");
+        assert_eq!(index.line_count(), 0);
+        assert_eq!(index.line_col(0, Encoding::Utf16), at(1, 1));
+        assert_eq!(index.offset(at(1, 1), Encoding::Utf16), 0);
+    }
+
+    #[test]
+    fn round_trip() {
+        let src: &str = "let é = \"字😀\"\r\n\tx += 1\n\n// 😀😀 done\nend";
+        let index: LineIndex = LineIndex::new(src);
+        for encoding in ALL {
+            for (offset, _) in src.char_indices().chain([(src.len(), ' ')]) {
+                // between the \r and \n of a \r\n isn't somewhere a cursor can be
+                if src[..offset].ends_with('\r') {
+                    continue;
+                }
+                let pos: LineCol = index.line_col(offset, encoding);
+                assert_eq!(index.offset(pos, encoding), offset, "{encoding:?} at {offset} ({pos:?})");
+            }
+        }
+    }
+}
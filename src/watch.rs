@@ -0,0 +1,228 @@
+//! waiting for files to change, for `check --watch`. on linux the kernel tells us (inotify, on the folders the
+//! files are in since editors like to save by writing a new file and renaming it over the old one). anywhere
+//! else, or if inotify isn't there, modification times get checked twice a second instead
+#[cfg(target_os = "linux")]
+use crate::project::manifest::canonical;
+use std::{
+    collections::HashMap,
+    fs::metadata,
+    path::{Path, PathBuf},
+    thread::sleep,
+    time::{Duration, SystemTime},
+};
+
+const POLL_EVERY: Duration = Duration::from_millis(500);
+
+pub struct Watcher {
+    // everything being watched, with when it last changed (None if it doesn't exist right now)
+    files: HashMap<PathBuf, Option<SystemTime>>,
+
+    #[cfg(target_os = "linux")]
+    inotify: Option<inotify::Inotify>,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl Default for Watcher {
+    fn default() -> Self {
+        Watcher::new()
+    }
+}
+
+impl Watcher {
+    pub fn new() -> Watcher {
+        Watcher {
+            files: HashMap::new(),
+            #[cfg(target_os = "linux")]
+            inotify: inotify::Inotify::new(),
+        }
+    }
+
+    /// watch exactly these files from now on
+    pub fn set(&mut self, files: &[PathBuf]) {
+        self.files = files.iter().map(|f| (f.clone(), modified(f))).collect();
+
+        #[cfg(target_os = "linux")]
+        if let Some(inotify) = &mut self.inotify {
+            for file in files {
+                let dir: &Path = file.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+                if !inotify.watch(dir) {
+                    // a folder we can't watch means we'd miss changes, so fall back to polling everything
+                    self.inotify = None;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// block until at least one watched file changes (or a new .stick file shows up next to one),
+    /// handing back what changed. a burst of changes (one save can be a few writes) comes back as one
+    pub fn wait(&mut self) -> Vec<PathBuf> {
+        loop {
+            #[cfg(target_os = "linux")]
+            if let Some(inotify) = &mut self.inotify {
+                let changed: Vec<PathBuf> = inotify
+                    .wait()
+                    .into_iter()
+                    .filter(|path| {
+                        self.files.keys().any(|f| same_file(f, path))
+                            || path.extension().is_some_and(|ext| ext == "stick")
+                    })
+                    .collect();
+                if !changed.is_empty() {
+                    return changed;
+                }
+                continue;
+            }
+
+            sleep(POLL_EVERY);
+            let mut changed: Vec<PathBuf> = Vec::new();
+            for (file, last) in self.files.iter_mut() {
+                let now: Option<SystemTime> = modified(file);
+                if now != *last {
+                    *last = now;
+                    changed.push(file.clone());
+                }
+            }
+            if !changed.is_empty() {
+                return changed;
+            }
+        }
+    }
+}
+
+// inotify hands back `dir/name` for whatever dir we gave it, which might not be spelled like the path we have
+#[cfg(target_os = "linux")]
+fn same_file(a: &Path, b: &Path) -> bool {
+    a == b || (a.file_name() == b.file_name() && a.parent().map(canonical) == b.parent().map(canonical))
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use std::{
+        collections::HashMap,
+        ffi::{CString, OsStr, c_char, c_int, c_ulong},
+        fs::File,
+        io::{ErrorKind, Read},
+        os::{
+            fd::{AsRawFd, FromRawFd},
+            unix::ffi::OsStrExt,
+        },
+        path::{Path, PathBuf},
+    };
+
+    // from <sys/inotify.h> and <poll.h>
+    const IN_NONBLOCK: c_int = 0o4000;
+    const IN_CLOEXEC: c_int = 0o2000000;
+    const IN_CLOSE_WRITE: u32 = 0x8;
+    const IN_MOVED_TO: u32 = 0x80;
+    const IN_CREATE: u32 = 0x100;
+    const IN_DELETE: u32 = 0x200;
+    const POLLIN: i16 = 0x1;
+
+    // how long things have to stay quiet before a burst of events counts as over
+    const SETTLE_MS: c_int = 50;
+
+    #[repr(C)]
+    struct PollFd {
+        fd: c_int,
+        events: i16,
+        revents: i16,
+    }
+
+    unsafe extern "C" {
+        fn inotify_init1(flags: c_int) -> c_int;
+        fn inotify_add_watch(fd: c_int, path: *const c_char, mask: u32) -> c_int;
+        fn poll(fds: *mut PollFd, nfds: c_ulong, timeout: c_int) -> c_int;
+    }
+
+    pub struct Inotify {
+        file: File,
+
+        // watch descriptors to the folder they watch
+        dirs: HashMap<c_int, PathBuf>,
+    }
+
+    impl Inotify {
+        /// None if the kernel won't give us one (too many open already, or no inotify at all)
+        pub fn new() -> Option<Inotify> {
+            // SAFETY: no pointers involved, a negative result is an error and anything else is a fresh fd we own
+            let fd: c_int = unsafe { inotify_init1(IN_NONBLOCK | IN_CLOEXEC) };
+            if fd < 0 {
+                return None;
+            }
+            Some(Inotify {
+                // SAFETY: fd was just opened and nothing else has it, the File closes it on drop
+                file: unsafe { File::from_raw_fd(fd) },
+                dirs: HashMap::new(),
+            })
+        }
+
+        /// start watching a folder (watching one twice is fine, the kernel hands back the same descriptor)
+        pub fn watch(&mut self, dir: &Path) -> bool {
+            let Ok(path) = CString::new(dir.as_os_str().as_bytes()) else {
+                return false;
+            };
+            let mask: u32 = IN_CLOSE_WRITE | IN_MOVED_TO | IN_CREATE | IN_DELETE;
+            // SAFETY: path is a valid nul terminated string that outlives the call
+            let wd: c_int = unsafe { inotify_add_watch(self.file.as_raw_fd(), path.as_ptr(), mask) };
+            if wd < 0 {
+                return false;
+            }
+            self.dirs.insert(wd, dir.to_path_buf());
+            true
+        }
+
+        // wait for the fd to have something, up to timeout ms (-1 is forever)
+        fn ready(&self, timeout: c_int) -> bool {
+            let mut fds: PollFd = PollFd {
+                fd: self.file.as_raw_fd(),
+                events: POLLIN,
+                revents: 0,
+            };
+            // SAFETY: fds is one valid PollFd for the length of the call
+            unsafe { poll(&mut fds, 1, timeout) > 0 }
+        }
+
+        /// block until something happens in a watched folder, then keep collecting until it settles down.
+        /// hands back the path of everything that got touched
+        pub fn wait(&mut self) -> Vec<PathBuf> {
+            let mut changed: Vec<PathBuf> = Vec::new();
+            let mut timeout: c_int = -1;
+            let mut buf: [u8; 4096] = [0; 4096];
+            while self.ready(timeout) {
+                timeout = SETTLE_MS;
+                loop {
+                    let len: usize = match self.file.read(&mut buf) {
+                        Ok(0) => break,
+                        Ok(len) => len,
+                        Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                        Err(_) => break,
+                    };
+
+                    // struct inotify_event { int wd; uint32_t mask, cookie, len; char name[len]; }, back to back
+                    let mut at: usize = 0;
+                    while at + 16 <= len {
+                        let field = |i: usize| <[u8; 4]>::try_from(&buf[at + i..at + i + 4]).unwrap_or_default();
+                        let wd: c_int = c_int::from_ne_bytes(field(0));
+                        let name_len: usize = u32::from_ne_bytes(field(12)) as usize;
+                        let name: &[u8] = &buf[at + 16..(at + 16 + name_len).min(len)];
+                        let name: &[u8] = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+                        if let Some(dir) = self.dirs.get(&wd)
+                            && !name.is_empty()
+                        {
+                            let path: PathBuf = dir.join(OsStr::from_bytes(name));
+                            if !changed.contains(&path) {
+                                changed.push(path);
+                            }
+                        }
+                        at += 16 + name_len;
+                    }
+                }
+            }
+            changed
+        }
+    }
+}